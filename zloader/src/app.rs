@@ -3,16 +3,171 @@
 //! `ZLoaderApp` owns the plugin host and audio engine, lays out the UI,
 //! and drives the periodic idle/position-poll timer.
 
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use gpui::{div, prelude::*, px, rgb, Entity, IntoElement, SharedString, Window};
 use gpui_component::button::{Button, ButtonVariants};
-use gpui_component::slider::SliderEvent;
+use gpui_component::slider::{Slider, SliderEvent, SliderState};
 
-use crate::engine::AudioEngine;
+use crate::engine::{AudioEngine, Levels};
 use crate::host::{ParamInfo, PluginHost, PluginInfo};
-use crate::params::ParamsView;
-use crate::waveform::{WaveformPeaks, WaveformView};
+use crate::keyboard::{KeyboardEvent, KeyboardView};
+use crate::output::{AudioOutput, Producer};
+use crate::params::{ParamEvent, ParamFormat, ParamsView};
+use crate::signal::{TestSignal, Waveform};
+use crate::waveform::{WaveformEvent, WaveformPeaks, WaveformView};
+
+/// Frames rendered per real-time production pass in the poll loop.
+const RENDER_BLOCK: usize = 512;
+
+// ---------------------------------------------------------------------------
+// Source — what the plugin is being fed
+// ---------------------------------------------------------------------------
+
+/// The signal streamed through the plugin: either the decoded file or the
+/// built-in test generator.
+pub enum Source {
+    /// Decoded interleaved PCM with its channel count and a frame read cursor.
+    File {
+        samples: Vec<f32>,
+        channels: usize,
+        pos: usize,
+    },
+    /// Synthesized test signal (runs indefinitely).
+    Test(TestSignal),
+}
+
+impl Source {
+    /// Channel count this source presents.
+    fn channels(&self) -> usize {
+        match self {
+            Source::File { channels, .. } => *channels,
+            Source::Test(t) => t.channels,
+        }
+    }
+
+    /// Fill up to `frames` of each plane and return the number of frames
+    /// actually produced. The file source stops at end-of-stream; the test
+    /// source always produces the full block.
+    ///
+    /// Each plane is filled to the plugin's channel count: file channels are
+    /// folded/padded by repeating the last source channel, while the test
+    /// signal is mono and copied across every plane.
+    fn fill(&mut self, planes: &mut [Vec<f32>], frames: usize, sample_rate: f32) -> usize {
+        match self {
+            Source::File {
+                samples,
+                channels,
+                pos,
+            } => {
+                let total_frames = if *channels > 0 {
+                    samples.len() / *channels
+                } else {
+                    0
+                };
+                let n = total_frames.saturating_sub(*pos).min(frames);
+                for f in 0..n {
+                    let base = (*pos + f) * *channels;
+                    for (ch, plane) in planes.iter_mut().enumerate() {
+                        plane[f] = samples[base + ch.min(channels.saturating_sub(1))];
+                    }
+                }
+                *pos += n;
+                n
+            }
+            Source::Test(t) => {
+                t.fill(planes, frames, sample_rate);
+                frames
+            }
+        }
+    }
+
+    /// Reset the file read cursor (no-op for the generator).
+    fn seek(&mut self, frame: usize) {
+        if let Source::File { pos, .. } = self {
+            *pos = frame;
+        }
+    }
+
+    /// True when a file source has streamed its last frame. The generator never
+    /// ends.
+    fn at_end(&self) -> bool {
+        match self {
+            Source::File {
+                samples,
+                channels,
+                pos,
+            } => {
+                let total = if *channels > 0 {
+                    samples.len() / *channels
+                } else {
+                    0
+                };
+                *pos >= total
+            }
+            Source::Test(_) => false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Playlist
+// ---------------------------------------------------------------------------
+
+/// One decoded track in the playlist bin.
+pub struct PlaylistItem {
+    pub path: PathBuf,
+    pub samples: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: f64,
+    pub peaks: WaveformPeaks,
+}
+
+impl PlaylistItem {
+    /// Display name (file name, extension included) for the playlist UI.
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "track".to_string())
+    }
+}
+
+/// Ordered queue of tracks with a cursor, mirroring a playlist bin: the pending
+/// items live in `items`, the playing one is `items[current_index]`.
+#[derive(Default)]
+pub struct Playlist {
+    pub items: Vec<PlaylistItem>,
+    pub current_index: usize,
+    /// When true, advancing past the last track wraps to the first.
+    pub loop_all: bool,
+}
+
+impl Playlist {
+    /// The track the cursor points at, if any.
+    pub fn current(&self) -> Option<&PlaylistItem> {
+        self.items.get(self.current_index)
+    }
+
+    /// Step the cursor by `delta` (usually ±1), wrapping when `loop_all` is set.
+    /// Returns the new index, or `None` when the move runs off a non-looping end.
+    fn step(&self, delta: isize) -> Option<usize> {
+        if self.items.is_empty() {
+            return None;
+        }
+        let len = self.items.len() as isize;
+        let next = self.current_index as isize + delta;
+        if next >= 0 && next < len {
+            Some(next as usize)
+        } else if self.loop_all {
+            Some(next.rem_euclid(len) as usize)
+        } else {
+            None
+        }
+    }
+}
 
 // ---------------------------------------------------------------------------
 // AppState — owns the non-Send FFI resources
@@ -24,6 +179,356 @@ pub struct AppState {
     pub engine: AudioEngine,
     pub plugin_info: PluginInfo,
     pub params: Vec<ParamInfo>,
+    /// Real-time output stream; kept alive for the lifetime of the app.
+    pub output: AudioOutput,
+    /// Writing half of the ring feeding the cpal callback.
+    pub producer: Producer,
+    /// Signal currently streamed through the plugin.
+    pub source: Source,
+    /// Most recent generated input block (mono), for live waveform display.
+    /// Empty unless a test source is active.
+    pub live_block: Vec<f32>,
+    /// The file source set aside while the test generator is active, restored
+    /// when the user switches back to the file.
+    pub file_stash: Option<Source>,
+    /// Queue of loaded tracks and the cursor into it.
+    pub playlist: Playlist,
+    /// A–B loop region `[start, end)` in samples, if set by the user.
+    pub loop_region: Option<(u64, u64)>,
+    /// Whether the A–B region is actively looping playback.
+    pub loop_enabled: bool,
+    /// Parameter edits available to undo (most recent at the back).
+    pub(crate) undo_stack: VecDeque<ParamEdit>,
+    /// Parameter edits available to redo (most recent at the back).
+    pub(crate) redo_stack: VecDeque<ParamEdit>,
+    /// The parameter of the in-progress edit gesture, for drag coalescing.
+    pub(crate) pending_param: Option<u32>,
+    /// Wall-clock time of the last recorded edit, for the debounce window.
+    pub(crate) last_edit_at: Option<Instant>,
+    /// Transport tempo in BPM fed to the plugin (UI-settable, default 120).
+    pub tempo_bpm: f64,
+    /// Run state of the test generator, independent of the engine's file
+    /// transport. The generator has no clock of its own, so it keeps its own
+    /// play/stop flag rather than riding the file position (which ends at EOF).
+    pub test_playing: bool,
+}
+
+/// A single undoable parameter change.
+pub(crate) struct ParamEdit {
+    param_id: u32,
+    old_value: f64,
+    new_value: f64,
+}
+
+/// Consecutive edits to the same parameter within this window coalesce into one
+/// history entry, so a slider drag is a single undo step.
+const UNDO_DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// Maximum number of edits retained in the undo history.
+const UNDO_CAP: usize = 128;
+
+impl AppState {
+    /// Switch the active source. `Some(kind)` selects the test generator with
+    /// the given waveform; `None` restores the decoded file.
+    ///
+    /// The file buffer is stashed (not discarded) on the first switch to a test
+    /// source so toggling back resumes from the same read position.
+    pub fn select_source(&mut self, kind: Option<Waveform>) {
+        match kind {
+            Some(kind) => {
+                if let Source::File { .. } = self.source {
+                    let channels = self.source.channels();
+                    let prev =
+                        std::mem::replace(&mut self.source, Source::Test(TestSignal::new(channels)));
+                    self.file_stash = Some(prev);
+                }
+                if let Source::Test(t) = &mut self.source {
+                    t.kind = kind;
+                }
+                // The generator starts running the moment it is selected; it
+                // has no transport of its own to press play on.
+                self.test_playing = true;
+            }
+            None => {
+                if let Some(file) = self.file_stash.take() {
+                    self.source = file;
+                    self.live_block.clear();
+                }
+                self.test_playing = false;
+            }
+        }
+    }
+
+    /// Whether the active source is currently producing audio: the engine's
+    /// file transport for a file, or the generator's own run flag for a test
+    /// source.
+    pub fn is_running(&self) -> bool {
+        match &self.source {
+            Source::Test(_) => self.test_playing,
+            Source::File { .. } => self.engine.is_playing(),
+        }
+    }
+
+    /// Start playback of the active source.
+    pub fn play(&mut self) {
+        if matches!(self.source, Source::Test(_)) {
+            self.test_playing = true;
+        } else {
+            let _ = self.engine.play();
+        }
+    }
+
+    /// Stop playback of the active source.
+    pub fn stop(&mut self) {
+        if matches!(self.source, Source::Test(_)) {
+            self.test_playing = false;
+        } else {
+            self.engine.stop();
+        }
+    }
+
+    /// Waveform of the active test source, or `None` when the file is active.
+    pub fn active_waveform(&self) -> Option<Waveform> {
+        match &self.source {
+            Source::Test(t) => Some(t.kind),
+            Source::File { .. } => None,
+        }
+    }
+
+    /// Set the generator frequency in Hz (ignored unless a test source is active).
+    pub fn set_test_freq(&mut self, hz: f32) {
+        if let Source::Test(t) = &mut self.source {
+            t.freq = hz;
+        }
+    }
+
+    /// Set the generator volume in `[0.0, 1.0]` (ignored unless a test source
+    /// is active).
+    pub fn set_test_volume(&mut self, volume: f32) {
+        if let Source::Test(t) = &mut self.source {
+            t.volume = volume;
+        }
+    }
+
+    /// Set the transport tempo in BPM fed to the plugin each block.
+    pub fn set_tempo(&mut self, bpm: f32) {
+        self.tempo_bpm = bpm as f64;
+    }
+
+    /// Decode `path` and append it to the playlist.
+    pub fn add_track(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let decoded = crate::decoder::decode_file(&path)?;
+        let peaks = WaveformPeaks::from_samples(&decoded.samples, decoded.channels);
+        self.playlist.items.push(PlaylistItem {
+            path,
+            samples: decoded.samples,
+            channels: decoded.channels,
+            sample_rate: decoded.sample_rate,
+            peaks,
+        });
+        Ok(())
+    }
+
+    /// Make the track at `index` the active file source: load it into the
+    /// engine, point the source at its PCM, and reseek to 0. Exits the test
+    /// generator if it was active. Returns the track's peaks so the caller can
+    /// repoint the waveform, or `None` if the index is out of range.
+    pub fn select_track(&mut self, index: usize) -> Option<WaveformPeaks> {
+        let item = self.playlist.items.get(index)?;
+        let audio = crate::decoder::DecodedAudio {
+            samples: item.samples.clone(),
+            channels: item.channels,
+            sample_rate: item.sample_rate,
+        };
+        let peaks = item.peaks.clone();
+        let _ = self.engine.load_samples(&audio);
+        self.engine.seek(0);
+        self.source = Source::File {
+            samples: audio.samples,
+            channels: audio.channels,
+            pos: 0,
+        };
+        self.file_stash = None;
+        self.live_block.clear();
+        self.playlist.current_index = index;
+        Some(peaks)
+    }
+
+    /// Advance by `delta` (±1) through the playlist, honoring `loop_all`.
+    /// Returns the new track's peaks when the cursor moved.
+    pub fn step_track(&mut self, delta: isize) -> Option<WaveformPeaks> {
+        let index = self.playlist.step(delta)?;
+        self.select_track(index)
+    }
+
+    /// Store the A–B loop region and push it to the engine when looping is on.
+    pub fn set_loop_region(&mut self, start: u64, end: u64) {
+        self.loop_region = Some((start, end));
+        if self.loop_enabled {
+            self.engine.set_loop_region(start, end);
+        }
+    }
+
+    /// Drop the loop region entirely and stop looping.
+    pub fn clear_loop_region(&mut self) {
+        self.loop_region = None;
+        self.loop_enabled = false;
+        self.engine.clear_loop_region();
+    }
+
+    /// Turn looping on or off over the current region. Enabling with no region
+    /// set only arms the flag; the engine starts looping once a region is set.
+    pub fn set_loop_enabled(&mut self, enabled: bool) {
+        self.loop_enabled = enabled;
+        match (enabled, self.loop_region) {
+            (true, Some((start, end))) => self.engine.set_loop_region(start, end),
+            _ => self.engine.clear_loop_region(),
+        }
+    }
+
+    /// Enforce the A–B loop from the poll loop: once `position` passes B, jump
+    /// back to A. Returns the sample sought to, so the caller can realign the
+    /// waveform playhead.
+    pub fn enforce_loop(&mut self, position: u64) -> Option<u64> {
+        if !self.loop_enabled {
+            return None;
+        }
+        let (start, end) = self.loop_region?;
+        if position >= end {
+            self.engine.seek(start);
+            self.source.seek(start as usize);
+            Some(start)
+        } else {
+            None
+        }
+    }
+
+    /// Record a parameter edit for undo.
+    ///
+    /// Consecutive edits to the same parameter inside [`UNDO_DEBOUNCE`] are
+    /// folded into the existing entry (its `old_value` — the value before the
+    /// gesture began — is kept), so a drag yields one undo step. Any new edit
+    /// clears the redo history.
+    pub fn record_param_edit(&mut self, param_id: u32, old_value: f64, new_value: f64) {
+        let now = Instant::now();
+        let coalesce = self.pending_param == Some(param_id)
+            && self
+                .last_edit_at
+                .is_some_and(|t| now.duration_since(t) < UNDO_DEBOUNCE);
+        if coalesce {
+            if let Some(top) = self.undo_stack.back_mut() {
+                top.new_value = new_value;
+            }
+        } else {
+            self.undo_stack.push_back(ParamEdit {
+                param_id,
+                old_value,
+                new_value,
+            });
+            while self.undo_stack.len() > UNDO_CAP {
+                self.undo_stack.pop_front();
+            }
+        }
+        self.redo_stack.clear();
+        self.pending_param = Some(param_id);
+        self.last_edit_at = Some(now);
+    }
+
+    /// Undo the most recent edit, applying the restored value to the plugin.
+    /// Returns `(param_id, restored_value)` so the UI can update the slider.
+    pub fn undo(&mut self) -> Option<(u32, f64)> {
+        let edit = self.undo_stack.pop_back()?;
+        self.host.set_param_value(edit.param_id, edit.old_value);
+        self.pending_param = None;
+        let restored = (edit.param_id, edit.old_value);
+        self.redo_stack.push_back(edit);
+        Some(restored)
+    }
+
+    /// Redo the most recently undone edit. Returns `(param_id, value)`.
+    pub fn redo(&mut self) -> Option<(u32, f64)> {
+        let edit = self.redo_stack.pop_back()?;
+        self.host.set_param_value(edit.param_id, edit.new_value);
+        self.pending_param = None;
+        let restored = (edit.param_id, edit.new_value);
+        self.undo_stack.push_back(edit);
+        Some(restored)
+    }
+}
+
+impl AppState {
+    /// Render processed audio into the output ring while the active source is
+    /// running, keeping the device fed without blocking the callback.
+    ///
+    /// Runs on the main thread from the poll loop: as long as the ring has
+    /// room, read a block of source frames, push them through the plugin, and
+    /// interleave the result into the ring at the device channel count.
+    pub fn pump_output(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        let out_channels = self.output.channels as usize;
+        let source_channels = self.source.channels();
+        if source_channels == 0 || out_channels == 0 {
+            return;
+        }
+        let sample_rate = self.engine.sample_rate() as f32;
+        let is_test = matches!(self.source, Source::Test(_));
+
+        while self.producer.free() >= RENDER_BLOCK * out_channels {
+            // Push the current transport so the plugin's play state and
+            // position drive any tempo-synced behaviour for this block.
+            let transport = crate::host::Transport::from_position(
+                self.engine.position(),
+                self.engine.sample_rate(),
+                self.tempo_bpm,
+                true,
+                self.loop_enabled,
+            );
+            self.host.set_transport(transport);
+
+            // Fill per-channel input planes, padding or folding to the plugin's
+            // channel count. A file source stops producing at end-of-stream.
+            let plugin_channels = out_channels.max(source_channels);
+            let mut in_planes: Vec<Vec<f32>> = vec![vec![0.0; RENDER_BLOCK]; plugin_channels];
+            let frames = self.source.fill(&mut in_planes, RENDER_BLOCK, sample_rate);
+            if frames == 0 {
+                break;
+            }
+            for plane in &mut in_planes {
+                plane.truncate(frames);
+            }
+
+            // Capture the generated block so the UI can scope the live signal.
+            if is_test {
+                self.live_block.clear();
+                self.live_block.extend_from_slice(&in_planes[0]);
+            }
+
+            let mut out_planes: Vec<Vec<f32>> = vec![vec![0.0; frames]; plugin_channels];
+
+            let inputs: Vec<&[f32]> = in_planes.iter().map(|p| p.as_slice()).collect();
+            let mut outputs: Vec<&mut [f32]> =
+                out_planes.iter_mut().map(|p| p.as_mut_slice()).collect();
+            // Time the process callback to drive the DSP-load meter.
+            let t0 = std::time::Instant::now();
+            self.host.process(&inputs, &mut outputs, frames as u32);
+            self.engine
+                .record_process_time(t0.elapsed(), frames as u32, sample_rate as f64);
+
+            // Interleave the plugin output into the device channel layout.
+            let mut block = vec![0.0f32; frames * out_channels];
+            for f in 0..frames {
+                for ch in 0..out_channels {
+                    block[f * out_channels + ch] = out_planes[ch][f];
+                }
+            }
+            // Meter the block that goes to the device so the level display
+            // reflects exactly what is audible.
+            self.engine.record_output_levels(&block, out_channels);
+            self.producer.push(&block);
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -34,8 +539,19 @@ pub struct ZLoaderApp {
     state: Entity<AppState>,
     waveform: Entity<WaveformView>,
     params_view: Entity<ParamsView>,
+    keyboard: Entity<KeyboardView>,
     is_playing: bool,
     plugin_name: SharedString,
+    /// Latest post-plugin output levels, refreshed by the poll loop.
+    levels: Levels,
+    /// Latest DSP load `(avg_pct, peak_pct)`, refreshed by the poll loop.
+    dsp_load: (f32, f32),
+    /// Test-generator frequency control (Hz).
+    freq_slider: Entity<SliderState>,
+    /// Test-generator volume control `[0.0, 1.0]`.
+    volume_slider: Entity<SliderState>,
+    /// Transport tempo control in BPM.
+    tempo_slider: Entity<SliderState>,
 }
 
 impl ZLoaderApp {
@@ -50,11 +566,95 @@ impl ZLoaderApp {
 
         let params = state.read_with(cx, |s, _| s.params.clone());
 
+        // Ask the plugin to format each parameter up front: step labels for
+        // enum/stepped params and the current value string for sliders.
+        let formats = state.read_with(cx, |s, _| {
+            params
+                .iter()
+                .map(|p| {
+                    let steps = if p.is_enum() || p.is_stepped() {
+                        let lo = p.min_value.round() as i64;
+                        let hi = p.max_value.round() as i64;
+                        (lo..=hi)
+                            .map(|i| {
+                                s.host
+                                    .param_value_to_text(p.id, i as f64)
+                                    .unwrap_or_else(|| i.to_string())
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    let current = s.host.get_param_value(p.id).unwrap_or(p.default_value);
+                    let value_text = s
+                        .host
+                        .param_value_to_text(p.id, current)
+                        .unwrap_or_else(|| format!("{current:.2}"));
+                    ParamFormat { steps, value_text }
+                })
+                .collect::<Vec<_>>()
+        });
+
+        // Build the on-screen keyboard and forward notes to the plugin.
+        let keyboard = cx.new(KeyboardView::new);
+        let state_for_notes = state.clone();
+        cx.subscribe(&keyboard, move |_this, _kb, event: &KeyboardEvent, cx| {
+            state_for_notes.read_with(cx, |s, _| match *event {
+                KeyboardEvent::NoteOn(key, vel) => s.host.send_note_on(key, vel),
+                KeyboardEvent::NoteOff(key) => s.host.send_note_off(key),
+            });
+        })
+        .detach();
+
         // Build the waveform view.
         let waveform = cx.new(|_cx| WaveformView::new(peaks));
 
+        // Forward scrubbing and loop-region edits to the engine.
+        let state_for_waveform = state.clone();
+        cx.subscribe(
+            &waveform,
+            move |_this, _wf, event: &WaveformEvent, cx| {
+                state_for_waveform.update(cx, |s, _cx| match *event {
+                    WaveformEvent::Seek(sample) => {
+                        s.engine.seek(sample);
+                        s.source.seek(sample as usize);
+                    }
+                    WaveformEvent::SetLoopRegion(start, end) => {
+                        s.set_loop_region(start, end);
+                    }
+                    WaveformEvent::ClearLoopRegion => s.clear_loop_region(),
+                });
+            },
+        )
+        .detach();
+
         // Build the params view.
-        let params_view = cx.new(|cx| ParamsView::new(&params, cx));
+        let params_view = cx.new(|cx| ParamsView::new(&params, &formats, cx));
+
+        // Forward non-slider param changes (switches, segmented controls).
+        let state_for_events = state.clone();
+        cx.subscribe(
+            &params_view,
+            move |_this, pv, event: &ParamEvent, cx| {
+                let ParamEvent::Change(param_id, value) = *event;
+                let text = state_for_events.update(cx, |s, _| {
+                    let old = s.host.get_param_value(param_id).unwrap_or(value);
+                    s.host.set_param_value(param_id, value);
+                    s.record_param_edit(param_id, old, value);
+                    s.host.param_value_to_text(param_id, value)
+                });
+                pv.update(cx, |pv, cx| {
+                    if let Some(entry) = pv.sliders.iter_mut().find(|e| e.info.id == param_id) {
+                        entry.value = value;
+                    }
+                    if let Some(t) = text {
+                        pv.set_value_text(param_id, t);
+                    }
+                    cx.notify();
+                });
+            },
+        )
+        .detach();
 
         // Subscribe to slider changes and forward to the plugin host.
         // This runs on the main thread with full access to cx.
@@ -70,21 +670,67 @@ impl ZLoaderApp {
             .into_iter()
             .for_each(|(param_id, slider_entity)| {
                 let state_weak = state_for_params.downgrade();
+                let params_weak = params_view.downgrade();
                 cx.subscribe(
                     &slider_entity,
                     move |_this, _slider, event: &SliderEvent, cx| {
                         let SliderEvent::Change(value) = event;
                         let v = value.start() as f64;
                         if let Some(state_entity) = state_weak.upgrade() {
-                            state_entity.read_with(cx, |s, _| {
+                            let text = state_entity.update(cx, |s, _| {
+                                // Capture the pre-edit value for the undo record.
+                                let old = s.host.get_param_value(param_id).unwrap_or(v);
                                 s.host.set_param_value(param_id, v);
+                                s.record_param_edit(param_id, old, v);
+                                s.host.param_value_to_text(param_id, v)
                             });
+                            if let (Some(pv), Some(text)) = (params_weak.upgrade(), text) {
+                                pv.update(cx, |pv, cx| {
+                                    pv.set_value_text(param_id, text);
+                                    cx.notify();
+                                });
+                            }
                         }
                     },
                 )
                 .detach();
             });
 
+        // Test-signal generator controls: frequency and volume sliders that
+        // drive the source directly (no plugin parameter involved).
+        let freq_slider =
+            cx.new(|_cx| SliderState::new().min(20.0).max(2000.0).default_value(440.0));
+        let volume_slider =
+            cx.new(|_cx| SliderState::new().min(0.0).max(1.0).default_value(0.5));
+        let tempo_slider =
+            cx.new(|_cx| SliderState::new().min(20.0).max(300.0).default_value(120.0));
+        {
+            let state_weak = state.downgrade();
+            cx.subscribe(&freq_slider, move |_this, _s, event: &SliderEvent, cx| {
+                let SliderEvent::Change(value) = event;
+                if let Some(state_entity) = state_weak.upgrade() {
+                    state_entity.update(cx, |s, _| s.set_test_freq(value.start()));
+                }
+            })
+            .detach();
+            let state_weak = state.downgrade();
+            cx.subscribe(&volume_slider, move |_this, _s, event: &SliderEvent, cx| {
+                let SliderEvent::Change(value) = event;
+                if let Some(state_entity) = state_weak.upgrade() {
+                    state_entity.update(cx, |s, _| s.set_test_volume(value.start()));
+                }
+            })
+            .detach();
+            let state_weak = state.downgrade();
+            cx.subscribe(&tempo_slider, move |_this, _s, event: &SliderEvent, cx| {
+                let SliderEvent::Change(value) = event;
+                if let Some(state_entity) = state_weak.upgrade() {
+                    state_entity.update(cx, |s, _| s.set_tempo(value.start()));
+                }
+            })
+            .detach();
+        }
+
         // Spawn a repeating timer to poll playback position and call idle.
         let state_weak = state.downgrade();
         let waveform_weak = waveform.downgrade();
@@ -103,20 +749,72 @@ impl ZLoaderApp {
                     };
 
                     // Poll position and call idle.
-                    let (position, is_playing) = state_entity.update(cx, |s, _cx| {
+                    let (position, is_playing, levels, dsp_load, live, advanced) =
+                        state_entity.update(cx, |s, _cx| {
                         s.host.idle();
-                        (s.engine.position(), s.engine.is_playing())
+                        s.pump_output();
+
+                        // End-of-stream: auto-advance to the next playlist entry
+                        // (wrapping when loop-all is set). Keep playing across the
+                        // track change so the queue runs unattended.
+                        let advanced = if s.engine.is_playing() && s.source.at_end() {
+                            match s.step_track(1) {
+                                Some(peaks) => {
+                                    let _ = s.engine.play();
+                                    Some(peaks)
+                                }
+                                None => {
+                                    s.engine.stop();
+                                    None
+                                }
+                            }
+                        } else {
+                            None
+                        };
+
+                        // Enforce the A–B loop: wrap back to A once past B.
+                        s.enforce_loop(s.engine.position());
+
+                        // When the generator is active, hand the latest block to
+                        // the waveform so it scopes the live signal.
+                        let live = if s.active_waveform().is_some() && !s.live_block.is_empty() {
+                            Some(s.live_block.clone())
+                        } else {
+                            None
+                        };
+                        (
+                            s.engine.position(),
+                            s.is_running(),
+                            s.engine.output_levels(),
+                            s.engine.dsp_load(),
+                            live,
+                            advanced,
+                        )
                     });
 
-                    // Update waveform playhead.
+                    // Update waveform: a track change repoints the peaks; a live
+                    // generator scopes its block; otherwise move the playhead.
                     waveform_entity.update(cx, |w, cx| {
-                        w.position = position;
+                        if let Some(peaks) = advanced {
+                            w.peaks = peaks;
+                            w.start_sample = 0;
+                            w.end_sample = w.peaks.total_samples;
+                            w.position = 0;
+                            w.loop_region = None;
+                        } else {
+                            match &live {
+                                Some(block) => w.show_live_block(block, 1),
+                                None => w.position = position,
+                            }
+                        }
                         cx.notify();
                     });
 
                     // Update root view playing state.
                     this.update(cx, |app, cx| {
                         app.is_playing = is_playing;
+                        app.levels = levels;
+                        app.dsp_load = dsp_load;
                         cx.notify();
                     })
                     .is_err()
@@ -133,14 +831,31 @@ impl ZLoaderApp {
             state,
             waveform,
             params_view,
+            keyboard,
             is_playing: false,
             plugin_name,
+            levels: Levels::default(),
+            dsp_load: (0.0, 0.0),
+            freq_slider,
+            volume_slider,
+            tempo_slider,
         }
     }
 
+    fn on_select_source(
+        &mut self,
+        kind: Option<Waveform>,
+        _window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        self.state.update(cx, |s, _cx| s.select_source(kind));
+        cx.notify();
+    }
+
     fn on_rewind(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
         self.state.update(cx, |s, _cx| {
             s.engine.seek(0);
+            s.source.seek(0);
         });
         self.waveform.update(cx, |w, cx| {
             w.position = 0;
@@ -149,13 +864,184 @@ impl ZLoaderApp {
         cx.notify();
     }
 
+    fn on_toggle_loop(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        self.state.update(cx, |s, _cx| {
+            let enabled = !s.loop_enabled;
+            s.set_loop_enabled(enabled);
+        });
+        cx.notify();
+    }
+
+    fn on_clear_loop(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        self.state.update(cx, |s, _cx| s.clear_loop_region());
+        self.waveform.update(cx, |w, cx| {
+            w.loop_region = None;
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    /// Repoint the waveform view at a freshly selected track's peaks.
+    fn show_track(&mut self, peaks: WaveformPeaks, cx: &mut gpui::Context<Self>) {
+        self.waveform.update(cx, |w, cx| {
+            w.peaks = peaks;
+            w.start_sample = 0;
+            w.end_sample = w.peaks.total_samples;
+            w.position = 0;
+            w.loop_region = None;
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn on_prev(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let peaks = self.state.update(cx, |s, _cx| s.step_track(-1));
+        if let Some(peaks) = peaks {
+            self.show_track(peaks, cx);
+        }
+    }
+
+    fn on_next(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let peaks = self.state.update(cx, |s, _cx| s.step_track(1));
+        if let Some(peaks) = peaks {
+            self.show_track(peaks, cx);
+        }
+    }
+
+    fn on_add_track(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let Some(paths) = rfd::FileDialog::new()
+            .add_filter("audio", &["wav", "aiff", "aif", "flac", "ogg", "mp3"])
+            .pick_files()
+        else {
+            return;
+        };
+        self.state.update(cx, |s, _cx| {
+            for path in paths {
+                if let Err(e) = s.add_track(path) {
+                    eprintln!("Add to playlist failed: {e:#}");
+                }
+            }
+        });
+        cx.notify();
+    }
+
+    fn on_toggle_loop_all(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        self.state.update(cx, |s, _cx| {
+            s.playlist.loop_all = !s.playlist.loop_all;
+        });
+        cx.notify();
+    }
+
+    /// Apply an undo/redo-restored value to the slider and its label.
+    fn apply_restored(
+        &mut self,
+        param_id: u32,
+        value: f64,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        let text = self
+            .state
+            .read_with(cx, |s, _| s.host.param_value_to_text(param_id, value));
+        self.params_view.update(cx, |pv, cx| {
+            pv.set_value(param_id, value, window, cx);
+            if let Some(t) = text {
+                pv.set_value_text(param_id, t);
+            }
+            cx.notify();
+        });
+    }
+
+    fn on_undo(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if let Some((param_id, value)) = self.state.update(cx, |s, _cx| s.undo()) {
+            self.apply_restored(param_id, value, window, cx);
+        }
+    }
+
+    fn on_redo(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        if let Some((param_id, value)) = self.state.update(cx, |s, _cx| s.redo()) {
+            self.apply_restored(param_id, value, window, cx);
+        }
+    }
+
+    fn on_save_preset(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("zloader preset", &["zpreset"])
+            .set_file_name("preset.zpreset")
+            .save_file()
+        else {
+            return;
+        };
+        let result = self.state.read_with(cx, |s, _| {
+            let blob = s.host.save_state()?;
+            let bytes = crate::preset::encode(
+                &s.plugin_info.id,
+                &s.plugin_info.version,
+                &blob,
+            );
+            std::fs::write(&path, bytes).map_err(anyhow::Error::from)
+        });
+        if let Err(e) = result {
+            eprintln!("Save Preset failed: {e:#}");
+        }
+    }
+
+    fn on_load_preset(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("zloader preset", &["zpreset"])
+            .pick_file()
+        else {
+            return;
+        };
+        let result = self.state.update(cx, |s, _cx| {
+            let bytes = std::fs::read(&path)?;
+            let blob = crate::preset::decode(&bytes, &s.plugin_info.id)?;
+            s.host.load_state(&blob)?;
+            // Re-read every parameter so the sliders reflect the loaded state.
+            let values: Vec<(u32, f64)> = s
+                .params
+                .iter()
+                .filter_map(|p| s.host.get_param_value(p.id).map(|v| (p.id, v)))
+                .collect();
+            anyhow::Ok(values)
+        });
+        match result {
+            Ok(values) => {
+                self.params_view.update(cx, |pv, cx| {
+                    for (id, value) in values {
+                        pv.set_value(id, value, window, cx);
+                    }
+                });
+            }
+            Err(e) => eprintln!("Load Preset failed: {e:#}"),
+        }
+    }
+
+    fn on_bounce(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("WAV", &["wav"])
+            .set_file_name("bounce.wav")
+            .save_file()
+        else {
+            return;
+        };
+        // Bounce the active A–B region when one is looping, else the whole file.
+        let result = self.state.update(cx, |s, _cx| {
+            let region = s.loop_enabled.then(|| s.loop_region).flatten();
+            s.engine.render_to_file(&path, region)
+        });
+        if let Err(e) = result {
+            eprintln!("Bounce failed: {e:#}");
+        }
+    }
+
     fn on_play_stop(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) {
-        let is_playing = self.state.read_with(cx, |s, _| s.engine.is_playing());
+        let is_playing = self.state.read_with(cx, |s, _| s.is_running());
         self.state.update(cx, |s, _cx| {
             if is_playing {
-                s.engine.stop();
+                s.stop();
             } else {
-                let _ = s.engine.play();
+                s.play();
             }
         });
         self.is_playing = !is_playing;
@@ -163,6 +1049,80 @@ impl ZLoaderApp {
     }
 }
 
+/// Map a linear amplitude to a `[0.0, 1.0]` meter fraction on a dB scale, with
+/// the floor pinned at -60 dB so silence reads as an empty bar.
+fn meter_fraction(amp: f32) -> f32 {
+    if amp <= 0.0 {
+        return 0.0;
+    }
+    let db = 20.0 * amp.log10();
+    ((db + 60.0) / 60.0).clamp(0.0, 1.0)
+}
+
+/// One channel meter: an RMS fill with a peak-hold tick over a fixed track.
+fn meter_row(rms: f32, peak_hold: f32) -> impl IntoElement {
+    const TRACK: f32 = 104.0;
+    let fill = meter_fraction(rms) * TRACK;
+    let hold = meter_fraction(peak_hold) * TRACK;
+    div()
+        .relative()
+        .w(px(TRACK))
+        .h(px(8.0))
+        .bg(rgb(0x1a1a2a))
+        .child(div().absolute().left_0().top_0().w(px(fill)).h(px(8.0)).bg(rgb(0x33cc66)))
+        .child(
+            div()
+                .absolute()
+                .left(px((hold - 2.0).max(0.0)))
+                .top_0()
+                .w(px(2.0))
+                .h(px(8.0))
+                .bg(rgb(0xffcc33)),
+        )
+}
+
+/// Header DSP-load readout: a bar filled to the smoothed average with a tick
+/// at the decaying peak, plus the numeric `avg% (peak%)`.
+fn dsp_meter(load: (f32, f32)) -> impl IntoElement {
+    const TRACK: f32 = 80.0;
+    let (avg, peak) = load;
+    let fill = (avg / 100.0).clamp(0.0, 1.0) * TRACK;
+    let hold = (peak / 100.0).clamp(0.0, 1.0) * TRACK;
+    // Green under 70%, amber past it, red once a buffer overrun is likely.
+    let bar_color = if avg >= 90.0 {
+        rgb(0xff5544)
+    } else if avg >= 70.0 {
+        rgb(0xffcc33)
+    } else {
+        rgb(0x33cc66)
+    };
+    let label: SharedString = format!("DSP {avg:.0}% (peak {peak:.0}%)").into();
+    div()
+        .flex()
+        .flex_row()
+        .items_center()
+        .gap(px(6.0))
+        .flex_shrink_0()
+        .child(
+            div()
+                .relative()
+                .w(px(TRACK))
+                .h(px(8.0))
+                .bg(rgb(0x1a1a2a))
+                .child(div().absolute().left_0().top_0().w(px(fill)).h(px(8.0)).bg(bar_color))
+                .child(
+                    div()
+                        .absolute()
+                        .left(px((hold - 2.0).max(0.0)))
+                        .top_0()
+                        .w(px(2.0))
+                        .h(px(8.0))
+                        .bg(rgb(0xffffff)),
+                ),
+        )
+        .child(div().text_xs().text_color(rgb(0x888888)).child(label))
+}
+
 impl Render for ZLoaderApp {
     fn render(&mut self, window: &mut Window, cx: &mut gpui::Context<Self>) -> impl IntoElement {
         let play_label: SharedString = if self.is_playing {
@@ -173,6 +1133,56 @@ impl Render for ZLoaderApp {
         let plugin_name = self.plugin_name.clone();
         let waveform = self.waveform.clone();
         let params_view = self.params_view.clone();
+        let keyboard = self.keyboard.clone();
+        let active_waveform = self.state.read_with(cx, |s, _| s.active_waveform());
+        let freq_slider = self.freq_slider.clone();
+        let volume_slider = self.volume_slider.clone();
+        let tempo_slider = self.tempo_slider.clone();
+        let loop_enabled = self.state.read_with(cx, |s, _| s.loop_enabled);
+        let (loop_all, playlist_label): (bool, SharedString) = self.state.read_with(cx, |s, _| {
+            let total = s.playlist.items.len();
+            let label = match s.playlist.current() {
+                Some(item) => format!("{}/{} — {}", s.playlist.current_index + 1, total, item.name()),
+                None => "empty".to_string(),
+            };
+            (s.playlist.loop_all, label.into())
+        });
+
+        // Source selector: the decoded file plus one button per generator wave.
+        let mut source_buttons = div().flex().flex_row().flex_wrap().gap(px(4.0));
+        let mut file_btn = Button::new("src_file").label("File").on_click(
+            cx.listener(|app, _ev, window, cx| app.on_select_source(None, window, cx)),
+        );
+        if active_waveform.is_none() {
+            file_btn = file_btn.primary();
+        }
+        source_buttons = source_buttons.child(file_btn);
+        for (i, kind) in Waveform::ALL.into_iter().enumerate() {
+            let mut btn = Button::new(("src_wave", i)).label(kind.label()).on_click(
+                cx.listener(move |app, _ev, window, cx| {
+                    app.on_select_source(Some(kind), window, cx)
+                }),
+            );
+            if active_waveform == Some(kind) {
+                btn = btn.primary();
+            }
+            source_buttons = source_buttons.child(btn);
+        }
+
+        let source_column = div()
+            .flex()
+            .flex_col()
+            .gap(px(8.0))
+            .w(px(140.0))
+            .flex_shrink_0()
+            .child(div().text_sm().text_color(rgb(0x888888)).child("Source"))
+            .child(source_buttons)
+            .child(div().text_xs().text_color(rgb(0x888888)).child("Frequency"))
+            .child(Slider::new(&freq_slider))
+            .child(div().text_xs().text_color(rgb(0x888888)).child("Volume"))
+            .child(Slider::new(&volume_slider))
+            .child(div().text_xs().text_color(rgb(0x888888)).child("Tempo (BPM)"))
+            .child(Slider::new(&tempo_slider));
 
         div()
             .flex()
@@ -182,19 +1192,26 @@ impl Render for ZLoaderApp {
             .text_color(rgb(0xffffff))
             .p(px(16.0))
             .gap(px(16.0))
-            // Header: plugin name
+            // Header: plugin name + DSP-load meter
             .child(
-                div().flex().flex_row().items_center().gap(px(8.0)).child(
-                    div()
-                        .text_size(px(20.0))
-                        .size_full()
-                        .bg(rgb(0x0000FF))
-                        .text_color(rgb(0xffffff))
-                        .child("test".clone()),
-                ),
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap(px(8.0))
+                    .child(
+                        div()
+                            .text_size(px(20.0))
+                            .flex_1()
+                            .text_color(rgb(0xffffff))
+                            .child(plugin_name),
+                    )
+                    .child(dsp_meter(self.dsp_load)),
             )
             // Waveform
             .child(waveform)
+            // Virtual keyboard
+            .child(keyboard)
             // Bottom: transport + params
             .child(
                 div()
@@ -217,6 +1234,11 @@ impl Render for ZLoaderApp {
                                     .flex()
                                     .flex_row()
                                     .gap(px(8.0))
+                                    .child(Button::new("prev").label("|<").on_click(cx.listener(
+                                        |app, _ev, window, cx| {
+                                            app.on_prev(window, cx);
+                                        },
+                                    )))
                                     .child(Button::new("rewind").label("<<").on_click(cx.listener(
                                         |app, _ev, window, cx| {
                                             app.on_rewind(window, cx);
@@ -229,9 +1251,115 @@ impl Render for ZLoaderApp {
                                             .on_click(cx.listener(|app, _ev, window, cx| {
                                                 app.on_play_stop(window, cx);
                                             })),
-                                    ),
+                                    )
+                                    .child(Button::new("next").label(">|").on_click(cx.listener(
+                                        |app, _ev, window, cx| {
+                                            app.on_next(window, cx);
+                                        },
+                                    ))),
+                            )
+                            .child(div().text_sm().text_color(rgb(0x888888)).child("Loop (A–B)"))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_row()
+                                    .gap(px(8.0))
+                                    .child({
+                                        let mut btn = Button::new("loop_toggle")
+                                            .label("Loop")
+                                            .on_click(cx.listener(|app, _ev, window, cx| {
+                                                app.on_toggle_loop(window, cx);
+                                            }));
+                                        if loop_enabled {
+                                            btn = btn.primary();
+                                        }
+                                        btn
+                                    })
+                                    .child(Button::new("loop_clear").label("Clear").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_clear_loop(window, cx);
+                                        }),
+                                    )),
+                            )
+                            .child(div().text_sm().text_color(rgb(0x888888)).child("Playlist"))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .gap(px(4.0))
+                                    .child(
+                                        div()
+                                            .text_xs()
+                                            .text_color(rgb(0x888888))
+                                            .child(playlist_label),
+                                    )
+                                    .child(Button::new("add_track").label("Add…").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_add_track(window, cx);
+                                        }),
+                                    ))
+                                    .child({
+                                        let mut btn = Button::new("loop_all").label("Loop All")
+                                            .on_click(cx.listener(|app, _ev, window, cx| {
+                                                app.on_toggle_loop_all(window, cx);
+                                            }));
+                                        if loop_all {
+                                            btn = btn.primary();
+                                        }
+                                        btn
+                                    }),
+                            )
+                            .child(div().text_sm().text_color(rgb(0x888888)).child("Edit"))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_row()
+                                    .gap(px(8.0))
+                                    .child(Button::new("undo").label("Undo").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_undo(window, cx);
+                                        }),
+                                    ))
+                                    .child(Button::new("redo").label("Redo").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_redo(window, cx);
+                                        }),
+                                    )),
+                            )
+                            .child(div().text_sm().text_color(rgb(0x888888)).child("Presets"))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .gap(px(8.0))
+                                    .child(Button::new("save_preset").label("Save Preset…").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_save_preset(window, cx);
+                                        }),
+                                    ))
+                                    .child(Button::new("load_preset").label("Load Preset…").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_load_preset(window, cx);
+                                        }),
+                                    ))
+                                    .child(Button::new("bounce").label("Bounce…").on_click(
+                                        cx.listener(|app, _ev, window, cx| {
+                                            app.on_bounce(window, cx);
+                                        }),
+                                    )),
+                            )
+                            .child(div().text_sm().text_color(rgb(0x888888)).child("Output"))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .gap(px(4.0))
+                                    .child(meter_row(self.levels.rms[0], self.levels.peak_hold[0]))
+                                    .child(meter_row(self.levels.rms[1], self.levels.peak_hold[1])),
                             ),
                     )
+                    // Source selector + generator controls
+                    .child(source_column)
                     // Parameter panel
                     .child(
                         div()
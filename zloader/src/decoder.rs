@@ -0,0 +1,374 @@
+//! Multi-format audio decoding layer sitting in front of the engine.
+//!
+//! The engine's `zpe_load_file` only understands WAV, so every format (WAV
+//! included) is decoded on the Rust side into interleaved `f32` PCM and handed to the
+//! engine via `zpe_load_samples`. Codecs are split into per-format modules
+//! behind a common [`Decoder`] trait; registering a new codec means adding a
+//! module and a line to [`decoders`] — `AudioEngine` never changes.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+
+// ---------------------------------------------------------------------------
+// Decoded buffer
+// ---------------------------------------------------------------------------
+
+/// Fully decoded audio: interleaved `f32` PCM plus its format.
+#[derive(Clone)]
+pub struct DecodedAudio {
+    pub samples: Vec<f32>,
+    pub channels: usize,
+    pub sample_rate: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Decoder trait
+// ---------------------------------------------------------------------------
+
+/// A single-format audio decoder.
+pub trait Decoder {
+    /// Short codec name for diagnostics.
+    fn name(&self) -> &'static str;
+
+    /// True if this decoder recognizes the file by extension or magic bytes.
+    fn matches(&self, ext: &str, magic: &[u8]) -> bool;
+
+    /// Decode the whole file into interleaved `f32` PCM.
+    fn decode(&self, data: &[u8]) -> Result<DecodedAudio>;
+}
+
+/// The registered decoders, tried in order.
+fn decoders() -> Vec<Box<dyn Decoder>> {
+    vec![
+        Box::new(wav::WavDecoder),
+        Box::new(aiff::AiffDecoder),
+        Box::new(flac::FlacDecoder),
+        Box::new(ogg::OggDecoder),
+        Box::new(mp3::Mp3Decoder),
+    ]
+}
+
+/// Decode `path`, dispatching to the first decoder that recognizes it.
+pub fn decode_file(path: &Path) -> Result<DecodedAudio> {
+    let data = std::fs::read(path)?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let magic = &data[..data.len().min(12)];
+
+    for decoder in decoders() {
+        if decoder.matches(&ext, magic) {
+            return decoder.decode(&data);
+        }
+    }
+    bail!("no decoder recognized {:?}", path)
+}
+
+// ---------------------------------------------------------------------------
+// WAV
+// ---------------------------------------------------------------------------
+
+mod wav {
+    use super::*;
+
+    pub struct WavDecoder;
+
+    impl Decoder for WavDecoder {
+        fn name(&self) -> &'static str {
+            "wav"
+        }
+
+        fn matches(&self, ext: &str, magic: &[u8]) -> bool {
+            ext == "wav" || (magic.len() >= 12 && &magic[0..4] == b"RIFF" && &magic[8..12] == b"WAVE")
+        }
+
+        fn decode(&self, data: &[u8]) -> Result<DecodedAudio> {
+            let (samples, channels, sample_rate) = parse(data)?;
+            Ok(DecodedAudio {
+                samples,
+                channels,
+                sample_rate,
+            })
+        }
+    }
+
+    /// Extract f32 samples, channel count and sample rate from a WAV file.
+    fn parse(data: &[u8]) -> Result<(Vec<f32>, usize, f64)> {
+        if data.len() < 44 {
+            bail!("WAV file too small");
+        }
+        if &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            bail!("Not a valid RIFF/WAVE file");
+        }
+
+        let mut pos = 12usize;
+        let mut channels: u16 = 0;
+        let mut bits: u16 = 0;
+        let mut audio_format: u16 = 0;
+        let mut sample_rate: u32 = 0;
+        let mut data_start = 0usize;
+        let mut data_len = 0usize;
+
+        while pos + 8 <= data.len() {
+            let chunk_id = &data[pos..pos + 4];
+            let chunk_size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into()?) as usize;
+            pos += 8;
+
+            if chunk_id == b"fmt " {
+                if chunk_size >= 16 {
+                    audio_format = u16::from_le_bytes(data[pos..pos + 2].try_into()?);
+                    channels = u16::from_le_bytes(data[pos + 2..pos + 4].try_into()?);
+                    sample_rate = u32::from_le_bytes(data[pos + 4..pos + 8].try_into()?);
+                    bits = u16::from_le_bytes(data[pos + 14..pos + 16].try_into()?);
+                }
+            } else if chunk_id == b"data" {
+                data_start = pos;
+                data_len = chunk_size;
+                break;
+            }
+
+            pos += chunk_size;
+            if chunk_size % 2 != 0 {
+                pos += 1;
+            }
+        }
+
+        if data_start == 0 || channels == 0 {
+            bail!("Could not find fmt/data chunks in WAV file");
+        }
+
+        let raw = &data[data_start..data_start.saturating_add(data_len).min(data.len())];
+        let samples: Vec<f32> = match (audio_format, bits) {
+            (3, 32) => raw
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect(),
+            (1, 16) => raw
+                .chunks_exact(2)
+                .map(|b| i16::from_le_bytes(b.try_into().unwrap()) as f32 / 32768.0)
+                .collect(),
+            (1, 24) => raw
+                .chunks_exact(3)
+                .map(|b| (i32::from_le_bytes([b[0], b[1], b[2], 0]) >> 8) as f32 / 8388608.0)
+                .collect(),
+            (1, 32) => raw
+                .chunks_exact(4)
+                .map(|b| i32::from_le_bytes(b.try_into().unwrap()) as f32 / 2147483648.0)
+                .collect(),
+            _ => bail!("Unsupported WAV format: audio_format={audio_format}, bits={bits}"),
+        };
+
+        Ok((samples, channels as usize, sample_rate as f64))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AIFF (big-endian, Apple)
+// ---------------------------------------------------------------------------
+
+mod aiff {
+    use super::*;
+
+    pub struct AiffDecoder;
+
+    impl Decoder for AiffDecoder {
+        fn name(&self) -> &'static str {
+            "aiff"
+        }
+
+        fn matches(&self, ext: &str, magic: &[u8]) -> bool {
+            matches!(ext, "aiff" | "aif" | "aifc")
+                || (magic.len() >= 12 && &magic[0..4] == b"FORM" && &magic[8..12] == b"AIFF")
+        }
+
+        fn decode(&self, data: &[u8]) -> Result<DecodedAudio> {
+            if data.len() < 12 || &data[0..4] != b"FORM" || &data[8..12] != b"AIFF" {
+                bail!("Not a valid AIFF file");
+            }
+            let mut pos = 12usize;
+            let mut channels: u16 = 0;
+            let mut bits: u16 = 0;
+            let mut sample_rate = 0.0f64;
+            let mut ssnd_start = 0usize;
+            let mut ssnd_len = 0usize;
+
+            while pos + 8 <= data.len() {
+                let id = &data[pos..pos + 4];
+                let size = u32::from_be_bytes(data[pos + 4..pos + 8].try_into()?) as usize;
+                pos += 8;
+                if id == b"COMM" {
+                    channels = u16::from_be_bytes(data[pos..pos + 2].try_into()?);
+                    bits = u16::from_be_bytes(data[pos + 6..pos + 8].try_into()?);
+                    sample_rate = extended_to_f64(&data[pos + 8..pos + 18]);
+                } else if id == b"SSND" {
+                    // Skip the 8-byte offset/blocksize header.
+                    ssnd_start = pos + 8;
+                    ssnd_len = size.saturating_sub(8);
+                }
+                pos += size + (size & 1);
+            }
+
+            if channels == 0 || ssnd_start == 0 {
+                bail!("Could not find COMM/SSND chunks in AIFF file");
+            }
+            let raw = &data[ssnd_start..ssnd_start.saturating_add(ssnd_len).min(data.len())];
+            let samples: Vec<f32> = match bits {
+                16 => raw
+                    .chunks_exact(2)
+                    .map(|b| i16::from_be_bytes(b.try_into().unwrap()) as f32 / 32768.0)
+                    .collect(),
+                24 => raw
+                    .chunks_exact(3)
+                    .map(|b| (i32::from_be_bytes([b[0], b[1], b[2], 0]) >> 8) as f32 / 8388608.0)
+                    .collect(),
+                _ => bail!("Unsupported AIFF bit depth: {bits}"),
+            };
+            Ok(DecodedAudio {
+                samples,
+                channels: channels as usize,
+                sample_rate,
+            })
+        }
+    }
+
+    /// Decode an 80-bit IEEE 754 extended float (AIFF sample rate field).
+    fn extended_to_f64(bytes: &[u8]) -> f64 {
+        let sign = if bytes[0] & 0x80 != 0 { -1.0 } else { 1.0 };
+        let exponent = (((bytes[0] as u16 & 0x7f) << 8) | bytes[1] as u16) as i32;
+        let mantissa = u64::from_be_bytes(bytes[2..10].try_into().unwrap());
+        if exponent == 0 && mantissa == 0 {
+            return 0.0;
+        }
+        sign * mantissa as f64 * 2f64.powi(exponent - 16383 - 63)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// FLAC (via claxon)
+// ---------------------------------------------------------------------------
+
+mod flac {
+    use super::*;
+    use std::io::Cursor;
+
+    pub struct FlacDecoder;
+
+    impl Decoder for FlacDecoder {
+        fn name(&self) -> &'static str {
+            "flac"
+        }
+
+        fn matches(&self, ext: &str, magic: &[u8]) -> bool {
+            ext == "flac" || (magic.len() >= 4 && &magic[0..4] == b"fLaC")
+        }
+
+        fn decode(&self, data: &[u8]) -> Result<DecodedAudio> {
+            let mut reader = claxon::FlacReader::new(Cursor::new(data))
+                .map_err(|e| anyhow!("flac: {e}"))?;
+            let info = reader.streaminfo();
+            let scale = 1.0 / (1i64 << (info.bits_per_sample - 1)) as f32;
+            let mut samples = Vec::new();
+            for s in reader.samples() {
+                samples.push(s.map_err(|e| anyhow!("flac: {e}"))? as f32 * scale);
+            }
+            Ok(DecodedAudio {
+                samples,
+                channels: info.channels as usize,
+                sample_rate: info.sample_rate as f64,
+            })
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Ogg Vorbis (via lewton)
+// ---------------------------------------------------------------------------
+
+mod ogg {
+    use super::*;
+    use std::io::Cursor;
+
+    use lewton::inside_ogg::OggStreamReader;
+
+    pub struct OggDecoder;
+
+    impl Decoder for OggDecoder {
+        fn name(&self) -> &'static str {
+            "ogg"
+        }
+
+        fn matches(&self, ext: &str, magic: &[u8]) -> bool {
+            matches!(ext, "ogg" | "oga") || (magic.len() >= 4 && &magic[0..4] == b"OggS")
+        }
+
+        fn decode(&self, data: &[u8]) -> Result<DecodedAudio> {
+            let mut reader =
+                OggStreamReader::new(Cursor::new(data)).map_err(|e| anyhow!("ogg: {e}"))?;
+            let channels = reader.ident_hdr.audio_channels as usize;
+            let sample_rate = reader.ident_hdr.audio_sample_rate as f64;
+            let mut samples = Vec::new();
+            while let Some(pck) = reader
+                .read_dec_packet_itl()
+                .map_err(|e| anyhow!("ogg: {e}"))?
+            {
+                samples.extend(pck.into_iter().map(|s| s as f32 / 32768.0));
+            }
+            Ok(DecodedAudio {
+                samples,
+                channels,
+                sample_rate,
+            })
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MP3 (via minimp3)
+// ---------------------------------------------------------------------------
+
+mod mp3 {
+    use super::*;
+
+    pub struct Mp3Decoder;
+
+    impl Decoder for Mp3Decoder {
+        fn name(&self) -> &'static str {
+            "mp3"
+        }
+
+        fn matches(&self, ext: &str, magic: &[u8]) -> bool {
+            ext == "mp3"
+                || (magic.len() >= 3 && &magic[0..3] == b"ID3")
+                || (magic.len() >= 2 && magic[0] == 0xFF && (magic[1] & 0xE0) == 0xE0)
+        }
+
+        fn decode(&self, data: &[u8]) -> Result<DecodedAudio> {
+            let mut decoder = minimp3::Decoder::new(data);
+            let mut samples = Vec::new();
+            let mut channels = 0usize;
+            let mut sample_rate = 0.0f64;
+            loop {
+                match decoder.next_frame() {
+                    Ok(frame) => {
+                        channels = frame.channels;
+                        sample_rate = frame.sample_rate as f64;
+                        samples.extend(frame.data.iter().map(|&s| s as f32 / 32768.0));
+                    }
+                    Err(minimp3::Error::Eof) => break,
+                    Err(e) => bail!("mp3: {e}"),
+                }
+            }
+            if channels == 0 {
+                bail!("mp3: no frames decoded");
+            }
+            Ok(DecodedAudio {
+                samples,
+                channels,
+                sample_rate,
+            })
+        }
+    }
+}
@@ -2,9 +2,12 @@
 #![allow(dead_code)]
 
 use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
 use anyhow::{anyhow, bail, Result};
 
+use crate::decoder::{self, DecodedAudio};
 use crate::ffi;
 use crate::host::PluginHost;
 
@@ -12,6 +15,39 @@ use crate::host::PluginHost;
 // AudioEngine
 // ---------------------------------------------------------------------------
 
+/// Post-plugin output levels, in linear amplitude `[0.0, 1.0]` per channel.
+///
+/// `peak` is measured on the last rendered block and `rms` over a short
+/// running window of blocks by [`AudioEngine::record_output_levels`];
+/// `peak_hold` is maintained by the
+/// wrapper and decays over roughly 1.5 s so transient peaks stay visible long
+/// enough to read.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Levels {
+    /// Block peak per channel `[left, right]`.
+    pub peak: [f32; 2],
+    /// Windowed RMS per channel `[left, right]`.
+    pub rms: [f32; 2],
+    /// Decaying peak-hold per channel `[left, right]`.
+    pub peak_hold: [f32; 2],
+}
+
+/// Per-poll decay applied to the peak-hold. At the app's ~30 ms poll interval
+/// this falls ~60 dB over about 1.5 s.
+const PEAK_HOLD_DECAY: f32 = 0.87;
+
+/// Smoothing factor for the DSP-load average: the EWMA keeps this fraction of
+/// the previous value and folds in the rest from the latest block.
+const DSP_LOAD_SMOOTHING: f32 = 0.9;
+
+/// Per-block decay for the DSP-load peak, so a spike fades (to about 1% within
+/// a second at ~90 blocks/s) rather than sticking forever.
+const DSP_PEAK_DECAY: f32 = 0.95;
+
+/// Time constant, in seconds, of the running mean of squares behind the
+/// windowed output RMS.
+const RMS_WINDOW_SECS: f64 = 0.3;
+
 /// Safe wrapper around the z_plug_engine audio engine.
 ///
 /// Manages playback of a WAV file through an optional CLAP plugin using
@@ -21,6 +57,18 @@ use crate::host::PluginHost;
 /// The underlying `ZpeEngine` pointer is freed on `Drop`.
 pub struct AudioEngine {
     ptr: *mut ffi::ZpeEngine,
+    /// Decaying peak-hold state, updated by `output_levels`.
+    peak_hold: [f32; 2],
+    /// Exponentially-smoothed process-callback load, as an `f32` bit pattern.
+    /// Written from the process path, read lock-free by the UI.
+    dsp_avg: AtomicU32,
+    /// Decaying peak process-callback load, as an `f32` bit pattern.
+    dsp_peak: AtomicU32,
+    /// Block peak per channel `[left, right]`, as `f32` bit patterns. Written
+    /// from the render path, read lock-free by the UI.
+    level_peak: [AtomicU32; 2],
+    /// Windowed RMS per channel `[left, right]`, as `f32` bit patterns.
+    level_rms: [AtomicU32; 2],
 }
 
 impl Drop for AudioEngine {
@@ -41,17 +89,46 @@ impl AudioEngine {
         if ptr.is_null() {
             bail!("zpe_create returned NULL");
         }
-        Ok(Self { ptr })
+        Ok(Self {
+            ptr,
+            peak_hold: [0.0; 2],
+            dsp_avg: AtomicU32::new(0),
+            dsp_peak: AtomicU32::new(0),
+            level_peak: [AtomicU32::new(0), AtomicU32::new(0)],
+            level_rms: [AtomicU32::new(0), AtomicU32::new(0)],
+        })
     }
 
-    /// Load a WAV file for playback.
+    /// Load an audio file for playback.
     ///
-    /// Stops playback and resets position if a file was already loaded.
-    pub fn load_file(&mut self, path: &Path) -> Result<()> {
-        let path_cstr = path_to_cstring(path)?;
-        let ok = unsafe { ffi::zpe_load_file(self.ptr, path_cstr.as_ptr()) };
+    /// Decodes any supported format (WAV/AIFF/FLAC/OGG/MP3) to interleaved
+    /// f32 PCM on the Rust side and hands the buffer to the engine. Stops
+    /// playback and resets position if a file was already loaded. Returns the
+    /// decoded buffer so callers can reuse it (e.g. for waveform peaks).
+    pub fn load_file(&mut self, path: &Path) -> Result<DecodedAudio> {
+        let decoded = decoder::decode_file(path)?;
+        self.load_samples(&decoded)?;
+        Ok(decoded)
+    }
+
+    /// Load already-decoded interleaved f32 PCM for playback.
+    pub fn load_samples(&mut self, audio: &DecodedAudio) -> Result<()> {
+        let frames = if audio.channels > 0 {
+            (audio.samples.len() / audio.channels) as u64
+        } else {
+            0
+        };
+        let ok = unsafe {
+            ffi::zpe_load_samples(
+                self.ptr,
+                audio.samples.as_ptr(),
+                frames,
+                audio.channels as u32,
+                audio.sample_rate,
+            )
+        };
         if !ok {
-            bail!("zpe_load_file failed for {:?}", path);
+            bail!("zpe_load_samples failed");
         }
         Ok(())
     }
@@ -70,6 +147,33 @@ impl AudioEngine {
         unsafe { ffi::zpe_set_plugin(self.ptr, plugin_ptr) };
     }
 
+    /// Render the loaded audio through the attached plugin offline and write
+    /// the result to `out`.
+    ///
+    /// Processing runs faster-than-realtime over `region` as `[start, end)`
+    /// samples, or the whole file when `region` is `None`, flushing the plugin
+    /// tail past the end. The live playhead and play state are restored
+    /// afterward so interactive playback is unaffected.
+    pub fn render_to_file(&mut self, out: &Path, region: Option<(u64, u64)>) -> Result<()> {
+        let saved_position = self.position();
+        let was_playing = self.is_playing();
+
+        let (start, end) = region.unwrap_or((0, self.length()));
+        let out_cstr = path_to_cstring(out)?;
+        let ok = unsafe { ffi::zpe_render_offline(self.ptr, out_cstr.as_ptr(), start, end) };
+
+        // Restore the interactive playhead regardless of the outcome.
+        self.seek(saved_position);
+        if was_playing {
+            let _ = self.play();
+        }
+
+        if !ok {
+            bail!("zpe_render_offline failed for {:?}", out);
+        }
+        Ok(())
+    }
+
     /// Start playback.
     pub fn play(&mut self) -> Result<()> {
         let ok = unsafe { ffi::zpe_play(self.ptr) };
@@ -125,6 +229,129 @@ impl AudioEngine {
     pub fn set_looping(&mut self, enable: bool) {
         unsafe { ffi::zpe_set_looping(self.ptr, enable) };
     }
+
+    /// Loop between `[start, end)` instead of the whole file.
+    ///
+    /// Playback wraps from `end` back to `start` sample-accurately.
+    pub fn set_loop_region(&mut self, start: u64, end: u64) {
+        unsafe { ffi::zpe_set_loop_region(self.ptr, start, end) };
+    }
+
+    /// Clear any loop region, falling back to full-file / no-loop behavior.
+    pub fn clear_loop_region(&mut self) {
+        unsafe { ffi::zpe_set_loop_region(self.ptr, 0, 0) };
+    }
+
+    /// Record the post-plugin output levels for one rendered block.
+    ///
+    /// `block` is interleaved at `channels` channels — the same buffer the main
+    /// thread pushes to the output ring, so the meter measures exactly the
+    /// audio that reaches the device. The block peak and a windowed RMS (the
+    /// block's mean square folded into a running mean with a
+    /// [`RMS_WINDOW_SECS`] time constant) are computed per channel (only the
+    /// first two feed the stereo meter; a mono block drives both), then stored
+    /// as atomics so [`output_levels`] can read them from
+    /// the UI thread without locking.
+    ///
+    /// [`output_levels`]: Self::output_levels
+    pub fn record_output_levels(&self, block: &[f32], channels: usize) {
+        if channels == 0 {
+            return;
+        }
+        let frames = block.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        let mut peak = [0.0f32; 2];
+        let mut sum_sq = [0.0f64; 2];
+        for f in 0..frames {
+            for ch in 0..channels.min(2) {
+                let s = block[f * channels + ch];
+                peak[ch] = peak[ch].max(s.abs());
+                sum_sq[ch] += (s as f64) * (s as f64);
+            }
+        }
+        // A mono stream lights both meters from its single channel.
+        if channels == 1 {
+            peak[1] = peak[0];
+            sum_sq[1] = sum_sq[0];
+        }
+        let block_secs = frames as f64 / self.sample_rate().max(1.0);
+        let keep = (-block_secs / RMS_WINDOW_SECS).exp();
+        for ch in 0..2 {
+            let prev = f32::from_bits(self.level_rms[ch].load(Ordering::Relaxed)) as f64;
+            let mean_sq = prev * prev * keep + (sum_sq[ch] / frames as f64) * (1.0 - keep);
+            let rms = mean_sq.sqrt() as f32;
+            self.level_peak[ch].store(peak[ch].to_bits(), Ordering::Relaxed);
+            self.level_rms[ch].store(rms.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Read the current post-plugin output levels.
+    ///
+    /// Returns the block peak and windowed RMS per channel as last recorded by
+    /// [`record_output_levels`], plus a peak-hold that rises instantly to a new
+    /// peak and otherwise decays by [`PEAK_HOLD_DECAY`] each call. Intended to
+    /// be polled from the main-thread idle timer.
+    ///
+    /// [`record_output_levels`]: Self::record_output_levels
+    pub fn output_levels(&mut self) -> Levels {
+        let peak = [
+            f32::from_bits(self.level_peak[0].load(Ordering::Relaxed)),
+            f32::from_bits(self.level_peak[1].load(Ordering::Relaxed)),
+        ];
+        let rms = [
+            f32::from_bits(self.level_rms[0].load(Ordering::Relaxed)),
+            f32::from_bits(self.level_rms[1].load(Ordering::Relaxed)),
+        ];
+        for ch in 0..2 {
+            self.peak_hold[ch] = if peak[ch] >= self.peak_hold[ch] {
+                peak[ch]
+            } else {
+                self.peak_hold[ch] * PEAK_HOLD_DECAY
+            };
+        }
+        Levels {
+            peak,
+            rms,
+            peak_hold: self.peak_hold,
+        }
+    }
+
+    /// Record the wall-clock cost of one process callback.
+    ///
+    /// `process` is the time spent inside the plugin's process callback;
+    /// `frames`/`sample_rate` give the real-time budget for that block. The
+    /// load fraction `process / (frames / sample_rate)` is folded into an
+    /// exponentially-smoothed average ([`DSP_LOAD_SMOOTHING`]) and a decaying
+    /// peak ([`DSP_PEAK_DECAY`]), both stored as atomics so [`dsp_load`] can be
+    /// polled from the UI thread without locking.
+    ///
+    /// [`dsp_load`]: Self::dsp_load
+    pub fn record_process_time(&self, process: Duration, frames: u32, sample_rate: f64) {
+        if frames == 0 || sample_rate <= 0.0 {
+            return;
+        }
+        let budget = frames as f64 / sample_rate;
+        let load = (process.as_secs_f64() / budget) as f32;
+
+        let prev_avg = f32::from_bits(self.dsp_avg.load(Ordering::Relaxed));
+        let avg = prev_avg * DSP_LOAD_SMOOTHING + load * (1.0 - DSP_LOAD_SMOOTHING);
+        self.dsp_avg.store(avg.to_bits(), Ordering::Relaxed);
+
+        let prev_peak = f32::from_bits(self.dsp_peak.load(Ordering::Relaxed));
+        let peak = (prev_peak * DSP_PEAK_DECAY).max(load);
+        self.dsp_peak.store(peak.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Current DSP load as `(average, peak)` percentages of the per-block
+    /// real-time budget. A value near 100 means the plugin is close to
+    /// overrunning its buffer.
+    pub fn dsp_load(&self) -> (f32, f32) {
+        let avg = f32::from_bits(self.dsp_avg.load(Ordering::Relaxed)) * 100.0;
+        let peak = f32::from_bits(self.dsp_peak.load(Ordering::Relaxed)) * 100.0;
+        (avg, peak)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -101,11 +101,97 @@ impl Default for ZphParamInfo {
     }
 }
 
+// ---------------------------------------------------------------------------
+// Transport info struct (matches ZphTransport in z_plug_host.h)
+// ---------------------------------------------------------------------------
+
+/// Playhead and tempo info pushed to the plugin before each process block.
+/// Mirrors the relevant fields of `clap_event_transport`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ZphTransport {
+    pub song_pos_samples: u64,
+    pub song_pos_beats: f64,
+    pub tempo_bpm: f64,
+    pub bar_start_beats: f64,
+    pub time_sig_num: u16,
+    pub time_sig_denom: u16,
+    pub flags: u32,
+}
+
+/// `flags` bit: transport is playing.
+pub const ZPH_TRANSPORT_IS_PLAYING: u32 = 1 << 0;
+/// `flags` bit: a loop region is active.
+pub const ZPH_TRANSPORT_IS_LOOPING: u32 = 1 << 1;
+/// `flags` bit: transport is recording.
+pub const ZPH_TRANSPORT_IS_RECORDING: u32 = 1 << 2;
+
+// ---------------------------------------------------------------------------
+// Note event struct (matches ZphNoteEvent in z_plug_host.h)
+// ---------------------------------------------------------------------------
+
+/// A note event queued for the plugin, translated into a `clap_event_note`
+/// in the next process call's input event list.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ZphNoteEvent {
+    /// `ZPH_NOTE_ON` / `ZPH_NOTE_OFF` / `ZPH_NOTE_CHOKE`.
+    pub kind: u8,
+    pub port: u16,
+    pub channel: u16,
+    pub key: i16,
+    pub velocity: f64,
+    /// Sample offset within the next process block.
+    pub time: u32,
+}
+
+/// `kind`: a note-on event.
+pub const ZPH_NOTE_ON: u8 = 0;
+/// `kind`: a note-off event.
+pub const ZPH_NOTE_OFF: u8 = 1;
+/// `kind`: a note-choke event (immediate silence, no release).
+pub const ZPH_NOTE_CHOKE: u8 = 2;
+
+// ---------------------------------------------------------------------------
+// Plugin descriptor struct (matches ZphPluginDescriptor in z_plug_host.h)
+// ---------------------------------------------------------------------------
+
+/// Lightweight descriptor filled by `zph_scan_bundle` without instantiating
+/// the plugin. All fields are null-terminated strings stored inline.
+#[repr(C)]
+pub struct ZphPluginDescriptor {
+    pub id: [u8; 256],
+    pub name: [u8; 256],
+    pub vendor: [u8; 256],
+    /// CLAP features string (e.g. `"audio-effect;stereo"`).
+    pub features: [u8; 512],
+}
+
+impl Default for ZphPluginDescriptor {
+    fn default() -> Self {
+        Self {
+            id: [0u8; 256],
+            name: [0u8; 256],
+            vendor: [0u8; 256],
+            features: [0u8; 512],
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // z_plug_host extern "C" declarations
 // ---------------------------------------------------------------------------
 
 extern "C" {
+    /// Enumerate the plugins exported by a `.clap` bundle without
+    /// instantiating them. Fills up to `cap` entries of `out_infos` and
+    /// returns the total number the bundle exports.
+    pub fn zph_scan_bundle(
+        path: *const c_char,
+        out_infos: *mut ZphPluginDescriptor,
+        cap: u32,
+    ) -> u32;
+
     /// Load a .clap file and instantiate a plugin. Returns NULL on failure.
     /// `plugin_id` may be NULL to load the first available plugin.
     pub fn zph_load_plugin(path: *const c_char, plugin_id: *const c_char) -> *mut ZphPlugin;
@@ -163,6 +249,29 @@ extern "C" {
     /// Load plugin state from buffer.
     pub fn zph_load_state(plugin: *mut ZphPlugin, buffer: *const u8, size: u32) -> bool;
 
+    /// Format a parameter value as human-readable text into `buf`.
+    pub fn zph_param_value_to_text(
+        plugin: *const ZphPlugin,
+        param_id: u32,
+        value: f64,
+        buf: *mut c_char,
+        buf_len: u32,
+    ) -> bool;
+
+    /// Parse human-readable text back into a parameter value.
+    pub fn zph_param_text_to_value(
+        plugin: *const ZphPlugin,
+        param_id: u32,
+        text: *const c_char,
+        out: *mut f64,
+    ) -> bool;
+
+    /// Queue a note event for the next process call's input event list.
+    pub fn zph_queue_note_event(plugin: *mut ZphPlugin, event: *const ZphNoteEvent);
+
+    /// Set the transport/tempo info applied to subsequent process calls.
+    pub fn zph_set_transport(plugin: *mut ZphPlugin, transport: *const ZphTransport);
+
     /// Handle deferred plugin callbacks. Call periodically from the main thread.
     pub fn zph_idle(plugin: *mut ZphPlugin);
 }
@@ -181,9 +290,28 @@ extern "C" {
     /// Load a WAV file for playback.
     pub fn zpe_load_file(engine: *mut ZpeEngine, path: *const c_char) -> bool;
 
+    /// Load already-decoded interleaved f32 PCM for playback.
+    pub fn zpe_load_samples(
+        engine: *mut ZpeEngine,
+        samples: *const f32,
+        frames: u64,
+        channels: u32,
+        sample_rate: f64,
+    ) -> bool;
+
     /// Attach a CLAP plugin to the engine.
     pub fn zpe_set_plugin(engine: *mut ZpeEngine, plugin: *mut ZphPlugin);
 
+    /// Render the attached plugin offline (faster-than-realtime) over the
+    /// sample range `[start_sample, end_sample)` to a WAV file, flushing the
+    /// plugin tail past the end. Returns false on failure.
+    pub fn zpe_render_offline(
+        engine: *mut ZpeEngine,
+        out_path: *const c_char,
+        start_sample: u64,
+        end_sample: u64,
+    ) -> bool;
+
     /// Start playback.
     pub fn zpe_play(engine: *mut ZpeEngine) -> bool;
 
@@ -213,4 +341,8 @@ extern "C" {
 
     /// Enable or disable looping.
     pub fn zpe_set_looping(engine: *mut ZpeEngine, enable: bool);
+
+    /// Loop between `[start_sample, end_sample)` instead of the whole file.
+    /// Passing `start == end == 0` clears the region.
+    pub fn zpe_set_loop_region(engine: *mut ZpeEngine, start_sample: u64, end_sample: u64);
 }
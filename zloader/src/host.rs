@@ -12,6 +12,15 @@ use crate::ffi;
 // Param info (safe Rust representation)
 // ---------------------------------------------------------------------------
 
+/// `flags` bit: the parameter only takes integer steps.
+pub const PARAM_IS_STEPPED: u32 = 1 << 0;
+/// `flags` bit: the parameter wraps around (e.g. a phase/pan knob).
+pub const PARAM_IS_PERIODIC: u32 = 1 << 1;
+/// `flags` bit: each integer step maps to a named choice.
+pub const PARAM_IS_ENUM: u32 = 1 << 2;
+/// `flags` bit: a two-state on/off toggle.
+pub const PARAM_IS_BOOLEAN: u32 = 1 << 3;
+
 /// Safe Rust representation of a plugin parameter.
 #[derive(Debug, Clone)]
 pub struct ParamInfo {
@@ -24,6 +33,28 @@ pub struct ParamInfo {
     pub flags: u32,
 }
 
+impl ParamInfo {
+    /// True if the parameter only accepts discrete integer steps.
+    pub fn is_stepped(&self) -> bool {
+        self.flags & (PARAM_IS_STEPPED | PARAM_IS_ENUM | PARAM_IS_BOOLEAN) != 0
+    }
+
+    /// True if the parameter wraps around at its bounds.
+    pub fn is_periodic(&self) -> bool {
+        self.flags & PARAM_IS_PERIODIC != 0
+    }
+
+    /// True if each integer step maps to a named choice.
+    pub fn is_enum(&self) -> bool {
+        self.flags & PARAM_IS_ENUM != 0
+    }
+
+    /// True if the parameter is a two-state on/off toggle.
+    pub fn is_boolean(&self) -> bool {
+        self.flags & PARAM_IS_BOOLEAN != 0
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Plugin info (safe Rust representation)
 // ---------------------------------------------------------------------------
@@ -41,6 +72,74 @@ pub struct PluginInfo {
     pub latency_samples: u32,
 }
 
+// ---------------------------------------------------------------------------
+// Transport (safe Rust representation)
+// ---------------------------------------------------------------------------
+
+/// Transport/tempo info handed to the plugin each process block.
+#[derive(Debug, Clone, Copy)]
+pub struct Transport {
+    pub song_pos_samples: u64,
+    pub song_pos_beats: f64,
+    pub tempo_bpm: f64,
+    pub bar_start_beats: f64,
+    pub time_sig_num: u16,
+    pub time_sig_denom: u16,
+    pub playing: bool,
+    pub looping: bool,
+    pub recording: bool,
+}
+
+impl Transport {
+    /// Build a transport from a sample position, tempo and engine play state.
+    ///
+    /// Beats are derived from samples as
+    /// `beats = samples / sample_rate * tempo_bpm / 60`, and the bar start is
+    /// snapped to the most recent downbeat given the time signature.
+    pub fn from_position(
+        song_pos_samples: u64,
+        sample_rate: f64,
+        tempo_bpm: f64,
+        playing: bool,
+        looping: bool,
+    ) -> Self {
+        let song_pos_beats = if sample_rate > 0.0 {
+            song_pos_samples as f64 / sample_rate * tempo_bpm / 60.0
+        } else {
+            0.0
+        };
+        let time_sig_num = 4u16;
+        let time_sig_denom = 4u16;
+        let beats_per_bar = time_sig_num as f64;
+        let bar_start_beats = (song_pos_beats / beats_per_bar).floor() * beats_per_bar;
+        Self {
+            song_pos_samples,
+            song_pos_beats,
+            tempo_bpm,
+            bar_start_beats,
+            time_sig_num,
+            time_sig_denom,
+            playing,
+            looping,
+            recording: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Plugin descriptor (safe Rust representation)
+// ---------------------------------------------------------------------------
+
+/// Lightweight description of one plugin inside a `.clap` bundle, obtained
+/// without instantiating it. Returned by [`PluginHost::scan`].
+#[derive(Debug, Clone)]
+pub struct PluginDescriptor {
+    pub id: String,
+    pub name: String,
+    pub vendor: String,
+    pub features: String,
+}
+
 // ---------------------------------------------------------------------------
 // PluginHost
 // ---------------------------------------------------------------------------
@@ -86,6 +185,33 @@ impl PluginHost {
         Ok(Self { ptr })
     }
 
+    /// Enumerate the plugins exported by a `.clap` bundle without loading them.
+    ///
+    /// A single bundle commonly exports several plugins; use the returned
+    /// descriptors to choose which `id` to pass to [`load`](Self::load).
+    pub fn scan(path: &Path) -> Result<Vec<PluginDescriptor>> {
+        let path_cstr = path_to_cstring(path)?;
+        // First query the count, then fill that many descriptors.
+        let count = unsafe { ffi::zph_scan_bundle(path_cstr.as_ptr(), std::ptr::null_mut(), 0) };
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut raw: Vec<ffi::ZphPluginDescriptor> =
+            (0..count).map(|_| ffi::ZphPluginDescriptor::default()).collect();
+        let filled =
+            unsafe { ffi::zph_scan_bundle(path_cstr.as_ptr(), raw.as_mut_ptr(), count) };
+        Ok(raw
+            .iter()
+            .take(filled as usize)
+            .map(|d| PluginDescriptor {
+                id: cstr_bytes_to_string(&d.id),
+                name: cstr_bytes_to_string(&d.name),
+                vendor: cstr_bytes_to_string(&d.vendor),
+                features: cstr_bytes_to_string(&d.features),
+            })
+            .collect())
+    }
+
     /// Activate the plugin for audio processing.
     pub fn activate(&mut self, sample_rate: f64, max_frames: u32) -> Result<()> {
         let ok = unsafe { ffi::zph_activate(self.ptr, sample_rate, max_frames) };
@@ -172,6 +298,139 @@ impl PluginHost {
         unsafe { ffi::zph_set_param_value(self.ptr, param_id, value) };
     }
 
+    /// Format `value` for `param_id` using the plugin's own formatter
+    /// (e.g. `"−6.0 dB"`, `"440 Hz"`). Returns `None` if the plugin declines.
+    pub fn param_value_to_text(&self, param_id: u32, value: f64) -> Option<String> {
+        let mut buf = [0u8; 256];
+        let ok = unsafe {
+            ffi::zph_param_value_to_text(
+                self.ptr,
+                param_id,
+                value,
+                buf.as_mut_ptr() as *mut std::ffi::c_char,
+                buf.len() as u32,
+            )
+        };
+        if ok {
+            Some(cstr_bytes_to_string(&buf))
+        } else {
+            None
+        }
+    }
+
+    /// Parse `text` into a value for `param_id` using the plugin's parser.
+    pub fn param_text_to_value(&self, param_id: u32, text: &str) -> Option<f64> {
+        let cstr = CString::new(text).ok()?;
+        let mut value: f64 = 0.0;
+        let ok = unsafe {
+            ffi::zph_param_text_to_value(self.ptr, param_id, cstr.as_ptr(), &mut value)
+        };
+        if ok {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Queue a note-on event delivered to the plugin on the next process call.
+    pub fn send_note_on(&self, key: i16, velocity: f64) {
+        self.queue_note(ffi::ZPH_NOTE_ON, key, velocity);
+    }
+
+    /// Queue a note-off event delivered to the plugin on the next process call.
+    pub fn send_note_off(&self, key: i16) {
+        self.queue_note(ffi::ZPH_NOTE_OFF, key, 0.0);
+    }
+
+    fn queue_note(&self, kind: u8, key: i16, velocity: f64) {
+        let event = ffi::ZphNoteEvent {
+            kind,
+            port: 0,
+            channel: 0,
+            key,
+            velocity,
+            time: 0,
+        };
+        unsafe { ffi::zph_queue_note_event(self.ptr, &event) };
+    }
+
+    /// Set the transport info applied to subsequent process calls.
+    pub fn set_transport(&self, transport: Transport) {
+        let mut flags = 0u32;
+        if transport.playing {
+            flags |= ffi::ZPH_TRANSPORT_IS_PLAYING;
+        }
+        if transport.looping {
+            flags |= ffi::ZPH_TRANSPORT_IS_LOOPING;
+        }
+        if transport.recording {
+            flags |= ffi::ZPH_TRANSPORT_IS_RECORDING;
+        }
+        let raw = ffi::ZphTransport {
+            song_pos_samples: transport.song_pos_samples,
+            song_pos_beats: transport.song_pos_beats,
+            tempo_bpm: transport.tempo_bpm,
+            bar_start_beats: transport.bar_start_beats,
+            time_sig_num: transport.time_sig_num,
+            time_sig_denom: transport.time_sig_denom,
+            flags,
+        };
+        unsafe { ffi::zph_set_transport(self.ptr, &raw) };
+    }
+
+    /// Process one block of audio through the plugin.
+    ///
+    /// `inputs` and `outputs` are per-channel slices of equal length
+    /// (`frame_count` frames). The plugin reads from `inputs` and writes the
+    /// processed result into `outputs`. Safe to call from the audio thread.
+    pub fn process(
+        &self,
+        inputs: &[&[f32]],
+        outputs: &mut [&mut [f32]],
+        frame_count: u32,
+    ) -> ffi::ZphProcessStatus {
+        let channel_count = outputs.len() as u32;
+        let in_ptrs: Vec<*const f32> = inputs.iter().map(|c| c.as_ptr()).collect();
+        let out_ptrs: Vec<*mut f32> = outputs.iter_mut().map(|c| c.as_mut_ptr()).collect();
+        unsafe {
+            ffi::zph_process(
+                self.ptr,
+                in_ptrs.as_ptr(),
+                out_ptrs.as_ptr(),
+                channel_count,
+                frame_count,
+            )
+        }
+    }
+
+    /// Serialize the plugin's current state into an opaque blob.
+    ///
+    /// Calls `zph_save_state` once with a NULL buffer to query the required
+    /// size, then again into an allocation of that size.
+    pub fn save_state(&self) -> Result<Vec<u8>> {
+        let mut size: u32 = 0;
+        let ok = unsafe { ffi::zph_save_state(self.ptr, std::ptr::null_mut(), &mut size) };
+        if !ok {
+            bail!("zph_save_state failed to query size");
+        }
+        let mut buffer = vec![0u8; size as usize];
+        let ok = unsafe { ffi::zph_save_state(self.ptr, buffer.as_mut_ptr(), &mut size) };
+        if !ok {
+            bail!("zph_save_state failed to write state");
+        }
+        buffer.truncate(size as usize);
+        Ok(buffer)
+    }
+
+    /// Restore plugin state previously produced by [`save_state`].
+    pub fn load_state(&mut self, buffer: &[u8]) -> Result<()> {
+        let ok = unsafe { ffi::zph_load_state(self.ptr, buffer.as_ptr(), buffer.len() as u32) };
+        if !ok {
+            bail!("zph_load_state failed");
+        }
+        Ok(())
+    }
+
     /// Handle deferred plugin callbacks. Call periodically from the main thread.
     pub fn idle(&mut self) {
         unsafe { ffi::zph_idle(self.ptr) };
@@ -0,0 +1,186 @@
+//! On-screen piano keyboard for auditioning instrument plugins.
+//!
+//! Renders two octaves of keys in the same gpui layout style as
+//! [`ParamsView`](crate::params::ParamsView). Clicking a key — or pressing
+//! the mapped computer-keyboard key — emits a [`KeyboardEvent`] the app
+//! forwards to [`PluginHost::send_note_on`](crate::host::PluginHost::send_note_on)
+//! / `send_note_off`.
+
+use std::collections::HashSet;
+
+use gpui::{
+    div, prelude::*, px, rgb, FocusHandle, IntoElement, KeyDownEvent, KeyUpEvent, MouseButton,
+    MouseDownEvent, MouseUpEvent, Window,
+};
+
+/// Lowest MIDI key shown (C4).
+const BASE_KEY: i16 = 60;
+/// Number of octaves rendered.
+const OCTAVES: i16 = 2;
+/// Default velocity for on-screen/keyboard note-ons.
+const DEFAULT_VELOCITY: f64 = 0.8;
+
+/// Semitone offsets of the black keys within an octave.
+const BLACK_SEMITONES: [i16; 5] = [1, 3, 6, 8, 10];
+
+/// Computer-keyboard → semitone-offset mapping (one row, tracker style).
+const KEY_MAP: &[(&str, i16)] = &[
+    ("a", 0),
+    ("w", 1),
+    ("s", 2),
+    ("e", 3),
+    ("d", 4),
+    ("f", 5),
+    ("t", 6),
+    ("g", 7),
+    ("y", 8),
+    ("h", 9),
+    ("u", 10),
+    ("j", 11),
+    ("k", 12),
+];
+
+// ---------------------------------------------------------------------------
+// KeyboardEvent
+// ---------------------------------------------------------------------------
+
+/// Events emitted by [`KeyboardView`].
+pub enum KeyboardEvent {
+    NoteOn(i16, f64),
+    NoteOff(i16),
+}
+
+// ---------------------------------------------------------------------------
+// KeyboardView
+// ---------------------------------------------------------------------------
+
+/// A GPUI view rendering a playable piano keyboard.
+pub struct KeyboardView {
+    focus_handle: FocusHandle,
+    /// Keys currently held down, to drive highlighting and avoid retrigger.
+    held: HashSet<i16>,
+}
+
+impl gpui::EventEmitter<KeyboardEvent> for KeyboardView {}
+
+impl gpui::Focusable for KeyboardView {
+    fn focus_handle(&self, _cx: &gpui::App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl KeyboardView {
+    pub fn new(cx: &mut gpui::Context<Self>) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            held: HashSet::new(),
+        }
+    }
+
+    fn press(&mut self, key: i16, cx: &mut gpui::Context<Self>) {
+        if self.held.insert(key) {
+            cx.emit(KeyboardEvent::NoteOn(key, DEFAULT_VELOCITY));
+            cx.notify();
+        }
+    }
+
+    fn release(&mut self, key: i16, cx: &mut gpui::Context<Self>) {
+        if self.held.remove(&key) {
+            cx.emit(KeyboardEvent::NoteOff(key));
+            cx.notify();
+        }
+    }
+
+    fn is_black(key: i16) -> bool {
+        BLACK_SEMITONES.contains(&(((key - BASE_KEY) % 12 + 12) % 12))
+    }
+}
+
+impl Render for KeyboardView {
+    fn render(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) -> impl IntoElement {
+        let white_keys: Vec<i16> = (0..OCTAVES * 12)
+            .map(|i| BASE_KEY + i)
+            .filter(|&k| !Self::is_black(k))
+            .collect();
+        let white_w = 28.0f32;
+
+        // White keys in a flex row.
+        let mut keys_row = div().flex().flex_row().h(px(96.0));
+        for &key in &white_keys {
+            let held = self.held.contains(&key);
+            let bg = if held { rgb(0x4a9eff) } else { rgb(0xf0f0f0) };
+            keys_row = keys_row.child(
+                div()
+                    .w(px(white_w))
+                    .h_full()
+                    .bg(bg)
+                    .border_1()
+                    .border_color(rgb(0x333333))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _e: &MouseDownEvent, _w, cx| this.press(key, cx)),
+                    )
+                    .on_mouse_up(
+                        MouseButton::Left,
+                        cx.listener(move |this, _e: &MouseUpEvent, _w, cx| this.release(key, cx)),
+                    ),
+            );
+        }
+
+        // Black keys positioned over the seams between white keys.
+        let mut black_layer = div().absolute().top_0().left_0().h(px(60.0));
+        let mut white_index = 0.0f32;
+        for i in 0..OCTAVES * 12 {
+            let key = BASE_KEY + i;
+            if Self::is_black(key) {
+                let held = self.held.contains(&key);
+                let bg = if held { rgb(0x2a6ebf) } else { rgb(0x202020) };
+                let left = white_index * white_w - white_w * 0.3;
+                black_layer = black_layer.child(
+                    div()
+                        .absolute()
+                        .left(px(left))
+                        .w(px(white_w * 0.6))
+                        .h(px(60.0))
+                        .bg(bg)
+                        .border_1()
+                        .border_color(rgb(0x000000))
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _e: &MouseDownEvent, _w, cx| this.press(key, cx)),
+                        )
+                        .on_mouse_up(
+                            MouseButton::Left,
+                            cx.listener(move |this, _e: &MouseUpEvent, _w, cx| this.release(key, cx)),
+                        ),
+                );
+            } else {
+                white_index += 1.0;
+            }
+        }
+
+        div()
+            .track_focus(&self.focus_handle)
+            .relative()
+            .w_full()
+            .on_key_down(cx.listener(|this, e: &KeyDownEvent, _w, cx| {
+                if e.is_held {
+                    return;
+                }
+                if let Some(&(_, semis)) =
+                    KEY_MAP.iter().find(|(k, _)| *k == e.keystroke.key)
+                {
+                    this.press(BASE_KEY + semis, cx);
+                }
+            }))
+            .on_key_up(cx.listener(|this, e: &KeyUpEvent, _w, cx| {
+                if let Some(&(_, semis)) =
+                    KEY_MAP.iter().find(|(k, _)| *k == e.keystroke.key)
+                {
+                    this.release(BASE_KEY + semis, cx);
+                }
+            }))
+            .child(keys_row)
+            .child(black_layer)
+    }
+}
@@ -4,10 +4,15 @@
 //!   zloader <plugin.clap> <audio.wav>
 
 mod app;
+mod decoder;
 mod engine;
 mod ffi;
 mod host;
+mod keyboard;
+mod output;
 mod params;
+mod preset;
+mod signal;
 mod transport;
 mod waveform;
 
@@ -21,6 +26,7 @@ use gpui_component::{theme::Theme, Root};
 use app::{AppState, ZLoaderApp};
 use engine::AudioEngine;
 use host::PluginHost;
+use output::AudioOutput;
 use waveform::WaveformPeaks;
 
 // ---------------------------------------------------------------------------
@@ -35,6 +41,18 @@ struct Args {
 
     /// Path to a WAV audio file to play through the plugin.
     audio_file: PathBuf,
+
+    /// Render the processed audio to this WAV file and exit (no GUI).
+    #[arg(long, value_name = "out.wav")]
+    render: Option<PathBuf>,
+
+    /// Select a specific plugin id from a multi-plugin `.clap` bundle.
+    #[arg(long)]
+    plugin_id: Option<String>,
+
+    /// List the plugins exported by the bundle and exit.
+    #[arg(long)]
+    list: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -53,6 +71,24 @@ fn main() -> Result<()> {
 
     let plugin_path = args.plugin_path.clone();
     let audio_path = args.audio_file.clone();
+    let plugin_id = args.plugin_id.clone();
+
+    // Enumerate bundle contents and exit.
+    if args.list {
+        let descriptors = PluginHost::scan(&plugin_path)?;
+        if descriptors.is_empty() {
+            println!("No plugins found in {:?}", plugin_path);
+        }
+        for d in &descriptors {
+            println!("{}\t{} — {} [{}]", d.id, d.name, d.vendor, d.features);
+        }
+        return Ok(());
+    }
+
+    // Offline render mode bypasses the GUI entirely.
+    if let Some(out) = args.render.clone() {
+        return render_to_wav(&plugin_path, plugin_id.as_deref(), &audio_path, &out);
+    }
 
     Application::new().run(move |cx: &mut App| {
         // Load embedded Inter Variable font so text renders on all platforms.
@@ -63,7 +99,7 @@ fn main() -> Result<()> {
         gpui_component::init(cx);
 
         // Load plugin and audio on the main thread (FFI is not Send).
-        let (state_entity, peaks) = match setup(cx, &plugin_path, &audio_path) {
+        let (state_entity, peaks) = match setup(cx, &plugin_path, &audio_path, plugin_id.as_deref()) {
             Ok(v) => v,
             Err(e) => {
                 eprintln!("Error: {e:#}");
@@ -100,131 +136,224 @@ fn main() -> Result<()> {
 // Setup: load plugin + audio, build peak data
 // ---------------------------------------------------------------------------
 
-const SAMPLE_RATE: f64 = 44100.0;
 const BUFFER_SIZE: u32 = 512;
-/// Number of waveform display columns (pre-computed peak resolution).
-const WAVEFORM_COLUMNS: usize = 1200;
 
 fn setup(
     cx: &mut App,
     plugin_path: &PathBuf,
     audio_path: &PathBuf,
+    plugin_id: Option<&str>,
 ) -> Result<(gpui::Entity<AppState>, WaveformPeaks)> {
+    // Resolve which plugin in the bundle to load. If the bundle exports more
+    // than one and the caller didn't pick, list them and take the first.
+    let chosen_id = match plugin_id {
+        Some(id) => Some(id.to_string()),
+        None => {
+            let descriptors = PluginHost::scan(plugin_path)?;
+            if descriptors.len() > 1 {
+                eprintln!("Bundle exports {} plugins; loading the first.", descriptors.len());
+                eprintln!("Re-run with --plugin-id <id> to choose:");
+                for d in &descriptors {
+                    eprintln!("  {}\t{}", d.id, d.name);
+                }
+            }
+            descriptors.into_iter().next().map(|d| d.id)
+        }
+    };
+
     // Load the plugin.
-    let mut host = PluginHost::load(plugin_path, None)?;
+    let mut host = PluginHost::load(plugin_path, chosen_id.as_deref())?;
     let plugin_info = host.get_info()?;
     let params = host.get_params();
 
-    // Create the audio engine and load the WAV file.
-    let mut engine = AudioEngine::new(SAMPLE_RATE, BUFFER_SIZE)?;
-    engine.load_file(audio_path)?;
+    // Open the real-time output device first so its negotiated sample rate
+    // drives both the engine and the plugin activation, rather than assuming
+    // a hardcoded default the device may not support.
+    let (producer, consumer) = output::ring_buffer(BUFFER_SIZE as usize * 8);
+    let output = AudioOutput::open(consumer)?;
+    let sample_rate = output.sample_rate;
 
-    // Activate and start processing.
-    host.activate(SAMPLE_RATE, BUFFER_SIZE)?;
+    // Create the audio engine and decode the audio file (any supported format).
+    let mut engine = AudioEngine::new(sample_rate, BUFFER_SIZE)?;
+    let decoded = engine.load_file(audio_path)?;
+
+    // Activate and start processing at the device's sample rate.
+    host.activate(sample_rate, BUFFER_SIZE)?;
     host.start_processing()?;
 
     // Attach plugin to engine.
     engine.set_plugin(Some(&mut host));
 
-    // Read the WAV file for waveform peak computation.
-    let peaks = build_peaks(audio_path)?;
+    // Build waveform peaks and the real-time source buffer from the decoded
+    // PCM directly, rather than re-reading and re-parsing the file.
+    let samples = decoded.samples;
+    let channels = decoded.channels;
+    let sample_rate_decoded = decoded.sample_rate;
+    let peaks = WaveformPeaks::from_samples(&samples, channels);
+
+    // Seed the playlist with the file given on the command line.
+    let playlist = app::Playlist {
+        items: vec![app::PlaylistItem {
+            path: audio_path.clone(),
+            samples: samples.clone(),
+            channels,
+            sample_rate: sample_rate_decoded,
+            peaks: peaks.clone(),
+        }],
+        current_index: 0,
+        loop_all: false,
+    };
 
     let state = cx.new(|_cx| AppState {
         host,
         engine,
         plugin_info,
         params,
+        output,
+        producer,
+        source: app::Source::File {
+            samples,
+            channels,
+            pos: 0,
+        },
+        live_block: Vec::new(),
+        file_stash: None,
+        playlist,
+        loop_region: None,
+        loop_enabled: false,
+        undo_stack: std::collections::VecDeque::new(),
+        redo_stack: std::collections::VecDeque::new(),
+        pending_param: None,
+        last_edit_at: None,
+        tempo_bpm: 120.0,
+        test_playing: false,
     });
 
     Ok((state, peaks))
 }
 
-/// Read the WAV file and compute waveform peaks for display.
-fn build_peaks(path: &PathBuf) -> Result<WaveformPeaks> {
-    let data = std::fs::read(path)?;
-    let (samples, channels) = parse_wav_samples(&data)?;
-    let peaks = WaveformPeaks::from_samples(&samples, channels, WAVEFORM_COLUMNS);
-    Ok(peaks)
-}
+// ---------------------------------------------------------------------------
+// Offline render
+// ---------------------------------------------------------------------------
 
-/// Minimal WAV parser that extracts f32 samples from PCM/float WAV files.
-/// Returns (interleaved_f32_samples, channel_count).
-fn parse_wav_samples(data: &[u8]) -> Result<(Vec<f32>, usize)> {
-    if data.len() < 44 {
-        anyhow::bail!("WAV file too small");
-    }
+/// Headless bounce: stream `audio_path` through the plugin in `BUFFER_SIZE`
+/// blocks and write the processed output to `out` as a 32-bit float WAV.
+///
+/// After the source is exhausted the plugin is driven with silence until it
+/// returns [`ZphProcessStatus::Sleep`] or its reported latency plus a fixed
+/// tail has elapsed, so reverb/delay tails are captured.
+fn render_to_wav(
+    plugin_path: &PathBuf,
+    plugin_id: Option<&str>,
+    audio_path: &PathBuf,
+    out: &PathBuf,
+) -> Result<()> {
+    use ffi::ZphProcessStatus;
+
+    let mut host = PluginHost::load(plugin_path, plugin_id)?;
+    let info = host.get_info()?;
+
+    // Activate at the file's own rate so the bounce is sample-accurate: a
+    // 48 kHz source must be processed (and tagged) at 48 kHz, not the device
+    // default, or playback is pitch/tempo-shifted.
+    let decoded = decoder::decode_file(audio_path)?;
+    let sample_rate = decoded.sample_rate;
+    host.activate(sample_rate, BUFFER_SIZE)?;
+    host.start_processing()?;
 
-    if &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
-        anyhow::bail!("Not a valid RIFF/WAVE file");
-    }
+    let samples = decoded.samples;
+    let in_channels = decoded.channels.max(1);
+    let out_channels = info.output_channels.max(1) as usize;
+    let channels = out_channels.max(in_channels);
+
+    let total_frames = samples.len() / in_channels;
+    let block = BUFFER_SIZE as usize;
+    let mut rendered: Vec<f32> = Vec::with_capacity(total_frames * channels);
+
+    let mut pos = 0usize;
+    // Frames of silence to push past EOF to flush the plugin's tail.
+    let mut tail_remaining = info.latency_samples as usize + sample_rate as usize;
+
+    loop {
+        let source_frames = total_frames.saturating_sub(pos);
+        let frames = if source_frames > 0 {
+            source_frames.min(block)
+        } else {
+            tail_remaining.min(block)
+        };
+        if frames == 0 {
+            break;
+        }
 
-    let mut pos = 12usize;
-    let mut fmt_channels: u16 = 0;
-    let mut fmt_bits: u16 = 0;
-    let mut fmt_audio_format: u16 = 0;
-    let mut data_start = 0usize;
-    let mut data_len = 0usize;
-
-    while pos + 8 <= data.len() {
-        let chunk_id = &data[pos..pos + 4];
-        let chunk_size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into()?) as usize;
-        pos += 8;
-
-        if chunk_id == b"fmt " {
-            if chunk_size >= 16 {
-                fmt_audio_format = u16::from_le_bytes(data[pos..pos + 2].try_into()?);
-                fmt_channels = u16::from_le_bytes(data[pos + 2..pos + 4].try_into()?);
-                fmt_bits = u16::from_le_bytes(data[pos + 14..pos + 16].try_into()?);
+        let mut in_planes: Vec<Vec<f32>> = vec![vec![0.0; frames]; channels];
+        for f in 0..frames {
+            if pos + f < total_frames {
+                let base = (pos + f) * in_channels;
+                for (ch, plane) in in_planes.iter_mut().enumerate() {
+                    plane[f] = samples[base + ch.min(in_channels - 1)];
+                }
             }
-        } else if chunk_id == b"data" {
-            data_start = pos;
-            data_len = chunk_size;
-            break;
         }
+        let mut out_planes: Vec<Vec<f32>> = vec![vec![0.0; frames]; channels];
 
-        pos += chunk_size;
-        if chunk_size % 2 != 0 {
-            pos += 1;
+        let inputs: Vec<&[f32]> = in_planes.iter().map(|p| p.as_slice()).collect();
+        let mut outputs: Vec<&mut [f32]> =
+            out_planes.iter_mut().map(|p| p.as_mut_slice()).collect();
+        let status = host.process(&inputs, &mut outputs, frames as u32);
+
+        for f in 0..frames {
+            for plane in &out_planes {
+                rendered.push(plane[f]);
+            }
         }
-    }
 
-    if data_start == 0 || fmt_channels == 0 {
-        anyhow::bail!("Could not find fmt/data chunks in WAV file");
+        if source_frames > 0 {
+            pos += frames;
+        } else {
+            tail_remaining -= frames;
+            if status == ZphProcessStatus::Sleep {
+                break;
+            }
+        }
     }
 
-    let raw = &data[data_start..data_start.saturating_add(data_len).min(data.len())];
-    let channels = fmt_channels as usize;
-
-    let samples: Vec<f32> = match (fmt_audio_format, fmt_bits) {
-        (3, 32) => raw
-            .chunks_exact(4)
-            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
-            .collect(),
-        (1, 16) => raw
-            .chunks_exact(2)
-            .map(|b| {
-                let s = i16::from_le_bytes(b.try_into().unwrap());
-                s as f32 / 32768.0
-            })
-            .collect(),
-        (1, 24) => raw
-            .chunks_exact(3)
-            .map(|b| {
-                let s = i32::from_le_bytes([b[0], b[1], b[2], 0]) >> 8;
-                s as f32 / 8388608.0
-            })
-            .collect(),
-        (1, 32) => raw
-            .chunks_exact(4)
-            .map(|b| {
-                let s = i32::from_le_bytes(b.try_into().unwrap());
-                s as f32 / 2147483648.0
-            })
-            .collect(),
-        _ => anyhow::bail!(
-            "Unsupported WAV format: audio_format={fmt_audio_format}, bits={fmt_bits}"
-        ),
-    };
+    host.stop_processing();
+    write_wav_f32(out, &rendered, channels, sample_rate as u32)?;
+    println!(
+        "Rendered {} frames to {:?}",
+        rendered.len() / channels,
+        out
+    );
+    Ok(())
+}
+
+/// Write interleaved f32 samples as a 32-bit float RIFF/WAVE file.
+/// The exact inverse of the `(3, 32)` branch of the WAV decoder.
+fn write_wav_f32(path: &PathBuf, samples: &[f32], channels: usize, sample_rate: u32) -> Result<()> {
+    let channels = channels as u16;
+    let bits = 32u16;
+    let block_align = channels * (bits / 8);
+    let byte_rate = sample_rate * block_align as u32;
+    let data_len = (samples.len() * 4) as u32;
+
+    let mut out = Vec::with_capacity(44 + samples.len() * 4);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    out.extend_from_slice(b"fmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&3u16.to_le_bytes()); // audio_format = IEEE float
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&byte_rate.to_le_bytes());
+    out.extend_from_slice(&block_align.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    for &s in samples {
+        out.extend_from_slice(&s.to_le_bytes());
+    }
 
-    Ok((samples, channels))
+    std::fs::write(path, out)?;
+    Ok(())
 }
@@ -0,0 +1,173 @@
+//! Real-time audio output via [`cpal`].
+//!
+//! The engine's block-producing thread renders processed frames (the WAV
+//! streamed through the attached CLAP plugin) and pushes them into a
+//! lock-free SPSC ring buffer; cpal's audio callback pulls interleaved
+//! frames out of that ring straight into the device buffer. The callback
+//! never blocks and never allocates, so it can meet the real-time deadline
+//! even when the producer momentarily stalls (it simply outputs silence).
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+// ---------------------------------------------------------------------------
+// Lock-free SPSC ring buffer
+// ---------------------------------------------------------------------------
+
+/// Shared state behind a single-producer / single-consumer f32 ring buffer.
+///
+/// Capacity is rounded up to a power of two so index wrapping is a mask. One
+/// slot is always left empty to disambiguate full from empty, so a ring of
+/// `capacity` can hold `capacity - 1` samples.
+struct RingShared {
+    buf: Box<[std::cell::UnsafeCell<f32>]>,
+    mask: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// Safe: the `Producer` and `Consumer` split guarantees exactly one writer and
+// one reader, each touching disjoint ends of `buf` under acquire/release.
+unsafe impl Sync for RingShared {}
+unsafe impl Send for RingShared {}
+
+/// Writing half of the ring. Lives on the engine's producer thread.
+pub struct Producer {
+    shared: Arc<RingShared>,
+}
+
+/// Reading half of the ring. Lives inside cpal's audio callback.
+pub struct Consumer {
+    shared: Arc<RingShared>,
+}
+
+/// Allocate a ring buffer of `capacity` slots (rounded up to a power of two)
+/// and split it into a [`Producer`]/[`Consumer`] pair. One slot stays empty,
+/// so it holds one sample fewer than its slot count.
+pub fn ring_buffer(capacity: usize) -> (Producer, Consumer) {
+    let cap = capacity.next_power_of_two().max(2);
+    let buf = (0..cap)
+        .map(|_| std::cell::UnsafeCell::new(0.0))
+        .collect::<Vec<_>>()
+        .into_boxed_slice();
+    let shared = Arc::new(RingShared {
+        buf,
+        mask: cap - 1,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (
+        Producer {
+            shared: shared.clone(),
+        },
+        Consumer { shared },
+    )
+}
+
+impl Producer {
+    /// Push as many of `samples` as fit, returning the number written.
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        let head = shared.head.load(Ordering::Acquire);
+        let free = shared.mask.wrapping_sub(tail.wrapping_sub(head)) & shared.mask;
+        let n = samples.len().min(free);
+        for (i, &s) in samples.iter().take(n).enumerate() {
+            let idx = tail.wrapping_add(i) & shared.mask;
+            unsafe { *shared.buf[idx].get() = s };
+        }
+        shared
+            .tail
+            .store(tail.wrapping_add(n) & shared.mask, Ordering::Release);
+        n
+    }
+
+    /// Number of free sample slots currently available to the producer.
+    pub fn free(&self) -> usize {
+        let shared = &*self.shared;
+        let tail = shared.tail.load(Ordering::Relaxed);
+        let head = shared.head.load(Ordering::Acquire);
+        shared.mask.wrapping_sub(tail.wrapping_sub(head)) & shared.mask
+    }
+}
+
+impl Consumer {
+    /// Fill `out` from the ring, zero-filling any remainder if the ring runs
+    /// dry (an underrun). Returns the number of real samples popped.
+    fn fill(&mut self, out: &mut [f32]) -> usize {
+        let shared = &*self.shared;
+        let head = shared.head.load(Ordering::Relaxed);
+        let tail = shared.tail.load(Ordering::Acquire);
+        let avail = tail.wrapping_sub(head) & shared.mask;
+        let n = out.len().min(avail);
+        for (i, slot) in out.iter_mut().take(n).enumerate() {
+            let idx = head.wrapping_add(i) & shared.mask;
+            *slot = unsafe { *shared.buf[idx].get() };
+        }
+        for slot in out.iter_mut().skip(n) {
+            *slot = 0.0;
+        }
+        shared
+            .head
+            .store(head.wrapping_add(n) & shared.mask, Ordering::Release);
+        n
+    }
+}
+
+// ---------------------------------------------------------------------------
+// AudioOutput
+// ---------------------------------------------------------------------------
+
+/// An open real-time output stream and the negotiated device format.
+///
+/// Holds the live cpal [`Stream`](cpal::Stream); dropping it stops playback.
+pub struct AudioOutput {
+    _stream: cpal::Stream,
+    /// Sample rate the device agreed to, in Hz. Pass this into
+    /// `PluginHost::activate` and `zpe_create` instead of a hardcoded rate.
+    pub sample_rate: f64,
+    /// Number of interleaved output channels.
+    pub channels: u16,
+}
+
+impl AudioOutput {
+    /// Open the default output device, negotiate a format, and start pulling
+    /// interleaved frames from `consumer` in the audio callback.
+    pub fn open(consumer: Consumer) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| anyhow!("no default output device"))?;
+        let config = device
+            .default_output_config()
+            .map_err(|e| anyhow!("no default output config: {e}"))?;
+
+        let sample_rate = config.sample_rate().0 as f64;
+        let channels = config.channels();
+
+        let mut consumer = consumer;
+        let err_fn = |e| eprintln!("cpal stream error: {e}");
+        let stream = device
+            .build_output_stream(
+                &config.config(),
+                move |data: &mut [f32], _info: &cpal::OutputCallbackInfo| {
+                    consumer.fill(data);
+                },
+                err_fn,
+                None,
+            )
+            .map_err(|e| anyhow!("failed to build output stream: {e}"))?;
+        stream
+            .play()
+            .map_err(|e| anyhow!("failed to start output stream: {e}"))?;
+
+        Ok(Self {
+            _stream: stream,
+            sample_rate,
+            channels,
+        })
+    }
+}
@@ -1,35 +1,81 @@
-//! Parameter panel: a scrollable list of labeled sliders for each plugin parameter.
+//! Parameter panel: a labeled control for each plugin parameter.
+//!
+//! Continuous parameters render as a [`Slider`] with the plugin-formatted
+//! value (e.g. `"−6.0 dB"`, `"440 Hz"`) beside it; boolean parameters render
+//! as a [`Switch`]; stepped/enum parameters render as a segmented control
+//! built from the formatted text of each integer step.
 
 use gpui::{div, prelude::*, px, rgb, Entity, IntoElement, SharedString, Window};
+use gpui_component::button::{Button, ButtonVariants};
 use gpui_component::slider::{Slider, SliderState};
+use gpui_component::switch::Switch;
 
 use crate::host::ParamInfo;
 
 // ---------------------------------------------------------------------------
-// Per-parameter slider entry
+// Formatting data supplied by the host at construction time
 // ---------------------------------------------------------------------------
 
-/// Holds the GPUI entity for one parameter's slider state.
+/// Plugin-formatted display strings for one parameter.
+///
+/// Computed up front by [`PluginHost`](crate::host::PluginHost) because
+/// `ParamsView` has no access to the FFI layer itself.
+#[derive(Clone, Default)]
+pub struct ParamFormat {
+    /// One label per integer step, for enum/stepped params (else empty).
+    pub steps: Vec<String>,
+    /// The formatted current value (shown next to continuous sliders).
+    pub value_text: String,
+}
+
+// ---------------------------------------------------------------------------
+// Per-parameter entry
+// ---------------------------------------------------------------------------
+
+/// Holds the GPUI state for one parameter's control.
 pub struct ParamSlider {
     pub info: ParamInfo,
     pub slider: Entity<SliderState>,
+    /// Current value, tracked so toggles/segments know the live state.
+    pub value: f64,
+    /// Step labels for enum/stepped params (empty for continuous).
+    pub steps: Vec<SharedString>,
+    /// Plugin-formatted current value for display.
+    pub value_text: SharedString,
+}
+
+// ---------------------------------------------------------------------------
+// ParamEvent
+// ---------------------------------------------------------------------------
+
+/// Emitted when a non-slider control (switch / segment) changes a value.
+/// Slider changes are observed directly on each slider entity.
+pub enum ParamEvent {
+    Change(u32, f64),
 }
 
 // ---------------------------------------------------------------------------
 // ParamsView
 // ---------------------------------------------------------------------------
 
-/// Panel of parameter sliders.
+/// Panel of parameter controls.
 pub struct ParamsView {
     pub sliders: Vec<ParamSlider>,
 }
 
+impl gpui::EventEmitter<ParamEvent> for ParamsView {}
+
 impl ParamsView {
-    /// Build the params view from a list of parameter infos.
-    pub fn new(params: &[ParamInfo], cx: &mut gpui::Context<Self>) -> Self {
+    /// Build the params view from parameter infos and their formatted strings.
+    pub fn new(
+        params: &[ParamInfo],
+        formats: &[ParamFormat],
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
         let sliders = params
             .iter()
-            .map(|info| {
+            .enumerate()
+            .map(|(i, info)| {
                 let min = info.min_value as f32;
                 let max = info.max_value as f32;
                 let default = info.default_value as f32;
@@ -41,9 +87,14 @@ impl ParamsView {
                         .default_value(default)
                 });
 
+                let fmt = formats.get(i).cloned().unwrap_or_default();
+
                 ParamSlider {
                     info: info.clone(),
                     slider,
+                    value: info.default_value,
+                    steps: fmt.steps.into_iter().map(Into::into).collect(),
+                    value_text: fmt.value_text.into(),
                 }
             })
             .collect();
@@ -51,10 +102,88 @@ impl ParamsView {
         Self { sliders }
     }
 
+    /// Set the displayed value of the slider for `param_id`, if present.
+    ///
+    /// Used to reflect externally-driven value changes (preset loads, undo)
+    /// back into the UI without rebuilding the panel.
+    pub fn set_value(
+        &mut self,
+        param_id: u32,
+        value: f64,
+        window: &mut Window,
+        cx: &mut gpui::Context<Self>,
+    ) {
+        if let Some(entry) = self.sliders.iter_mut().find(|s| s.info.id == param_id) {
+            entry.value = value;
+            entry.slider.update(cx, |state, cx| {
+                state.set_value(value as f32, window, cx);
+            });
+        }
+    }
+
+    /// Update the plugin-formatted text shown for `param_id`.
+    pub fn set_value_text(&mut self, param_id: u32, text: String) {
+        if let Some(entry) = self.sliders.iter_mut().find(|s| s.info.id == param_id) {
+            entry.value_text = text.into();
+        }
+    }
+
+    fn render_row(entry: &ParamSlider, cx: &mut gpui::Context<Self>) -> gpui::Div {
+        let info = &entry.info;
+        let name: SharedString = info.name.clone().into();
+        let param_id = info.id;
+
+        let mut row = div()
+            .flex()
+            .flex_col()
+            .gap(px(4.0))
+            .w_full()
+            .child(div().text_sm().text_color(rgb(0xcccccc)).child(name));
+
+        if info.is_boolean() {
+            let checked = entry.value >= 0.5;
+            row = row.child(
+                Switch::new(("param_switch", param_id as usize))
+                    .checked(checked)
+                    .on_click(cx.listener(move |_this, checked: &bool, _window, cx| {
+                        let v = if *checked { 1.0 } else { 0.0 };
+                        cx.emit(ParamEvent::Change(param_id, v));
+                    })),
+            );
+        } else if !entry.steps.is_empty() {
+            let selected = (entry.value.round() as i64 - info.min_value.round() as i64).max(0);
+            let min = info.min_value.round() as i64;
+            let mut seg = div().flex().flex_row().gap(px(4.0)).flex_wrap();
+            for (i, label) in entry.steps.iter().enumerate() {
+                let value = (min + i as i64) as f64;
+                let mut button = Button::new(("param_seg", param_id as usize * 256 + i))
+                    .label(label.clone())
+                    .on_click(cx.listener(move |_this, _ev, _window, cx| {
+                        cx.emit(ParamEvent::Change(param_id, value));
+                    }));
+                if i as i64 == selected {
+                    button = button.primary();
+                }
+                seg = seg.child(button);
+            }
+            row = row.child(seg);
+        } else {
+            row = row
+                .child(Slider::new(&entry.slider))
+                .child(
+                    div()
+                        .text_xs()
+                        .text_color(rgb(0x888888))
+                        .child(entry.value_text.clone()),
+                );
+        }
+
+        row
+    }
 }
 
 impl Render for ParamsView {
-    fn render(&mut self, _window: &mut Window, _cx: &mut gpui::Context<Self>) -> impl IntoElement {
+    fn render(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) -> impl IntoElement {
         let mut rows = div()
             .flex()
             .flex_col()
@@ -63,23 +192,7 @@ impl Render for ParamsView {
             .overflow_hidden();
 
         for entry in &self.sliders {
-            let name: SharedString = entry.info.name.clone().into();
-            let slider_entity = entry.slider.clone();
-
-            let row = div()
-                .flex()
-                .flex_col()
-                .gap(px(4.0))
-                .w_full()
-                .child(
-                    div()
-                        .text_sm()
-                        .text_color(rgb(0xcccccc))
-                        .child(name),
-                )
-                .child(Slider::new(&slider_entity));
-
-            rows = rows.child(row);
+            rows = rows.child(Self::render_row(entry, cx));
         }
 
         rows
@@ -0,0 +1,147 @@
+//! `.zpreset` container format wrapping an opaque plugin state blob.
+//!
+//! The blob returned by [`PluginHost::save_state`](crate::host::PluginHost::save_state)
+//! is meaningless outside its originating plugin, so the container records the
+//! plugin `id` and `version` plus a CRC of the payload. [`decode`] refuses to
+//! hand a preset back unless the id matches and the CRC checks out, preventing
+//! a preset from being loaded into the wrong plugin.
+
+use anyhow::{bail, Result};
+
+/// Magic bytes at the head of every `.zpreset` file.
+const MAGIC: &[u8; 4] = b"ZPRE";
+/// Container format version.
+const FORMAT_VERSION: u32 = 1;
+
+/// Wrap `blob` in a container tagged with the plugin `id` and `version`.
+pub fn encode(id: &str, version: &str, blob: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(blob.len() + id.len() + version.len() + 32);
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    write_str(&mut out, id);
+    write_str(&mut out, version);
+    out.extend_from_slice(&crc32(blob).to_le_bytes());
+    out.extend_from_slice(&(blob.len() as u32).to_le_bytes());
+    out.extend_from_slice(blob);
+    out
+}
+
+/// Unwrap a container, verifying it was written for the plugin `id` and that
+/// the payload CRC matches. Returns the opaque state blob.
+pub fn decode(bytes: &[u8], id: &str) -> Result<Vec<u8>> {
+    let mut pos = 0usize;
+    if bytes.len() < 8 || &bytes[0..4] != MAGIC {
+        bail!("not a .zpreset file");
+    }
+    pos += 4;
+    let format = read_u32(bytes, &mut pos)?;
+    if format != FORMAT_VERSION {
+        bail!("unsupported .zpreset version {format}");
+    }
+    let preset_id = read_str(bytes, &mut pos)?;
+    let _preset_version = read_str(bytes, &mut pos)?;
+    if preset_id != id {
+        bail!("preset is for plugin '{preset_id}', not '{id}'");
+    }
+    let crc = read_u32(bytes, &mut pos)?;
+    let len = read_u32(bytes, &mut pos)? as usize;
+    if pos + len > bytes.len() {
+        bail!("truncated .zpreset payload");
+    }
+    let blob = bytes[pos..pos + len].to_vec();
+    if crc32(&blob) != crc {
+        bail!("preset CRC mismatch (corrupt file)");
+    }
+    Ok(blob)
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn read_u32(bytes: &[u8], pos: &mut usize) -> Result<u32> {
+    if *pos + 4 > bytes.len() {
+        bail!("truncated .zpreset header");
+    }
+    let v = u32::from_le_bytes(bytes[*pos..*pos + 4].try_into().unwrap());
+    *pos += 4;
+    Ok(v)
+}
+
+fn read_str(bytes: &[u8], pos: &mut usize) -> Result<String> {
+    let len = read_u32(bytes, pos)? as usize;
+    if *pos + len > bytes.len() {
+        bail!("truncated .zpreset string");
+    }
+    let s = String::from_utf8_lossy(&bytes[*pos..*pos + len]).into_owned();
+    *pos += len;
+    Ok(s)
+}
+
+/// Standard CRC-32 (IEEE 802.3 polynomial), computed without a lookup table.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xFFFF_FFFF;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_the_blob() {
+        let blob = b"\x00\x01\x02opaque plugin state\xff";
+        let bytes = encode("com.example.synth", "1.2.0", blob);
+        assert_eq!(decode(&bytes, "com.example.synth").unwrap(), blob);
+    }
+
+    #[test]
+    fn round_trips_an_empty_blob() {
+        let bytes = encode("com.example.synth", "1.0", &[]);
+        assert!(decode(&bytes, "com.example.synth").unwrap().is_empty());
+    }
+
+    #[test]
+    fn crc32_matches_known_value() {
+        // CRC-32 of "123456789" is the IEEE check value 0xCBF43926.
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn rejects_a_different_plugin_id() {
+        let bytes = encode("com.example.synth", "1.0", b"state");
+        assert!(decode(&bytes, "com.example.reverb").is_err());
+    }
+
+    #[test]
+    fn rejects_a_corrupted_payload() {
+        let mut bytes = encode("com.example.synth", "1.0", b"state");
+        *bytes.last_mut().unwrap() ^= 0xFF;
+        assert!(decode(&bytes, "com.example.synth").is_err());
+    }
+
+    #[test]
+    fn rejects_a_foreign_magic() {
+        assert!(decode(b"NOPE\x01\x00\x00\x00", "id").is_err());
+    }
+
+    #[test]
+    fn rejects_a_truncated_header() {
+        let bytes = encode("com.example.synth", "1.0", b"state");
+        assert!(decode(&bytes[..6], "com.example.synth").is_err());
+    }
+
+    #[test]
+    fn rejects_a_truncated_payload() {
+        let bytes = encode("com.example.synth", "1.0", b"state");
+        assert!(decode(&bytes[..bytes.len() - 2], "com.example.synth").is_err());
+    }
+}
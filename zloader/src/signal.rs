@@ -0,0 +1,116 @@
+//! Built-in test-signal generator.
+//!
+//! Lets a plugin be auditioned without importing audio: instead of streaming a
+//! decoded file, the engine can be fed a synthesized tone. Tonal modes advance
+//! a phase accumulator `phase += 2π·freq/sample_rate` per frame (wrapping at
+//! 2π); the noise mode emits scaled pseudo-random samples. The generated signal
+//! is mono and copied across every channel before it reaches `host.process`.
+
+use std::f32::consts::TAU;
+
+/// Selectable generator waveform.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Saw,
+    Noise,
+}
+
+impl Waveform {
+    /// Every waveform, in UI-display order.
+    pub const ALL: [Waveform; 4] = [
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Saw,
+        Waveform::Noise,
+    ];
+
+    /// Short label for the source selector.
+    pub fn label(self) -> &'static str {
+        match self {
+            Waveform::Sine => "Sine",
+            Waveform::Square => "Square",
+            Waveform::Saw => "Saw",
+            Waveform::Noise => "Noise",
+        }
+    }
+}
+
+/// A stateful test-signal oscillator.
+///
+/// Holds the tuning (waveform, frequency, volume, channel count) plus the phase
+/// accumulator and PRNG state carried across blocks so the signal is continuous.
+pub struct TestSignal {
+    pub kind: Waveform,
+    /// Oscillator frequency in Hz (ignored by [`Waveform::Noise`]).
+    pub freq: f32,
+    /// Output gain in `[0.0, 1.0]`.
+    pub volume: f32,
+    /// Number of channels the source presents.
+    pub channels: usize,
+    /// Phase in radians `[0, 2π)`.
+    phase: f32,
+    /// xorshift32 state for the noise mode.
+    rng: u32,
+}
+
+impl TestSignal {
+    /// A 440 Hz sine at half volume across `channels` channels.
+    pub fn new(channels: usize) -> Self {
+        Self {
+            kind: Waveform::Sine,
+            freq: 440.0,
+            volume: 0.5,
+            channels: channels.max(1),
+            phase: 0.0,
+            rng: 0x2545_f491,
+        }
+    }
+
+    /// Compute the next mono sample and advance the internal state.
+    fn next_sample(&mut self, sample_rate: f32) -> f32 {
+        let value = match self.kind {
+            Waveform::Sine => self.phase.sin(),
+            // Square: sign of the sine half-cycle.
+            Waveform::Square => {
+                if self.phase < std::f32::consts::PI {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            // Rising ramp from -1 to +1 over one cycle.
+            Waveform::Saw => self.phase / std::f32::consts::PI - 1.0,
+            // xorshift32 mapped to [-1, 1]; phase is irrelevant.
+            Waveform::Noise => {
+                self.rng ^= self.rng << 13;
+                self.rng ^= self.rng >> 17;
+                self.rng ^= self.rng << 5;
+                (self.rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+            }
+        };
+
+        if sample_rate > 0.0 {
+            self.phase += TAU * self.freq / sample_rate;
+            while self.phase >= TAU {
+                self.phase -= TAU;
+            }
+        }
+
+        value * self.volume
+    }
+
+    /// Fill `frames` of every plane with the generated (mono) signal.
+    ///
+    /// All planes receive the same samples, so the tone is identical on each
+    /// channel regardless of the plugin's channel layout.
+    pub fn fill(&mut self, planes: &mut [Vec<f32>], frames: usize, sample_rate: f32) {
+        for f in 0..frames {
+            let s = self.next_sample(sample_rate);
+            for plane in planes.iter_mut() {
+                plane[f] = s;
+            }
+        }
+    }
+}
@@ -1,9 +1,16 @@
 //! Waveform display using GPUI's `canvas()` element.
 //!
-//! Renders a pre-computed peak overview of the loaded audio file, with a
-//! playhead line indicating the current playback position. Clicking or
-//! dragging on the waveform emits a [`WaveformEvent::Seek`] event with the
-//! target sample position.
+//! Peaks are stored as a multi-resolution pyramid (mipmap): level 0 holds one
+//! min/max pair per small fixed bucket of frames, and each higher level folds
+//! adjacent pairs to halve the resolution. Rendering a view window picks the
+//! coarsest level fine enough for the current pixel density and aggregates its
+//! buckets into display columns, so zoom/scroll cost is O(visible pixels)
+//! rather than O(total samples).
+//!
+//! Clicking or dragging on the waveform emits a [`WaveformEvent::Seek`] event
+//! with the target sample position, mapped against the visible window.
+//! Shift-dragging instead defines a loop region ([`WaveformEvent::SetLoopRegion`]),
+//! drawn as a translucent highlight with draggable in/out handles.
 
 use gpui::{
     canvas, div, prelude::*, px, rgb, Background, BorderStyle, Bounds, Corners, Edges, Hsla,
@@ -11,73 +18,198 @@ use gpui::{
     Point, Size, Window,
 };
 
+/// Frames per bucket at the finest pyramid level.
+const BASE_BUCKET_FRAMES: usize = 256;
+
 // ---------------------------------------------------------------------------
 // Peak data
 // ---------------------------------------------------------------------------
 
-/// Pre-computed min/max peak pairs for waveform rendering.
-/// One entry per display column (pixel-width bucket).
+/// Summary of one bucket: peak extremes plus an RMS body level.
+#[derive(Clone, Copy, Default)]
+pub struct Bucket {
+    pub min: f32,
+    pub max: f32,
+    /// `sqrt(mean(sample^2))` across the bucket, in [0.0, 1.0].
+    pub rms: f32,
+}
+
+/// One resolution level of the peak pyramid.
+#[derive(Clone)]
+pub struct PeakLevel {
+    /// Number of source frames summarized by each bucket at this level.
+    pub bucket_frames: usize,
+    /// One summary per bucket.
+    pub peaks: Vec<Bucket>,
+}
+
+/// Amplitude mapping for the waveform display.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Linear amplitude: `|v|` maps directly to height.
+    Linear,
+    /// Logarithmic: `20*log10(|v|)` clamped to a floor, so low-level detail
+    /// is visible.
+    Db,
+}
+
+/// Default dB floor for [`ScaleMode::Db`].
+pub const DEFAULT_DB_FLOOR: f32 = -60.0;
+
+/// Multi-resolution min/max peak pyramid for waveform rendering.
 #[derive(Clone)]
 pub struct WaveformPeaks {
-    /// (min, max) pairs in [-1.0, 1.0], one per display column.
-    pub peaks: Vec<(f32, f32)>,
-    /// Total number of samples in the source file (for playhead math).
+    /// Pyramid levels, finest (level 0) first.
+    pub levels: Vec<PeakLevel>,
+    /// Total number of frames in the source file (for playhead math).
     pub total_samples: u64,
 }
 
 impl WaveformPeaks {
-    /// Build peak data from raw interleaved f32 samples.
+    /// Build a peak pyramid from raw interleaved f32 samples.
     ///
-    /// `num_columns` is the target display width in pixels. The samples are
-    /// divided into that many equal-sized buckets; min/max are computed per
-    /// bucket across all channels.
-    pub fn from_samples(samples: &[f32], channels: usize, num_columns: usize) -> Self {
+    /// Level 0 buckets [`BASE_BUCKET_FRAMES`] frames into one min/max pair;
+    /// each subsequent level folds adjacent pairs until a handful of buckets
+    /// cover the whole file.
+    pub fn from_samples(samples: &[f32], channels: usize) -> Self {
         let total_frames = if channels > 0 {
             samples.len() / channels
         } else {
             0
         };
-
         let total_samples = total_frames as u64;
 
-        if total_frames == 0 || num_columns == 0 {
+        if total_frames == 0 {
             return Self {
-                peaks: vec![(0.0, 0.0); num_columns.max(1)],
+                levels: vec![PeakLevel {
+                    bucket_frames: BASE_BUCKET_FRAMES,
+                    peaks: vec![Bucket::default()],
+                }],
                 total_samples,
             };
         }
 
-        let frames_per_col = (total_frames as f64 / num_columns as f64).max(1.0);
-        let mut peaks = Vec::with_capacity(num_columns);
-
-        for col in 0..num_columns {
-            let start_frame = (col as f64 * frames_per_col) as usize;
-            let end_frame = ((col + 1) as f64 * frames_per_col) as usize;
-            let end_frame = end_frame.min(total_frames);
-
-            let mut min = 0.0f32;
-            let mut max = 0.0f32;
-
-            for frame in start_frame..end_frame {
+        // Level 0: one min/max/RMS per BASE_BUCKET_FRAMES frames.
+        let num_buckets = total_frames.div_ceil(BASE_BUCKET_FRAMES);
+        let mut level0 = Vec::with_capacity(num_buckets);
+        for b in 0..num_buckets {
+            let start = b * BASE_BUCKET_FRAMES;
+            let end = (start + BASE_BUCKET_FRAMES).min(total_frames);
+            let (mut min, mut max) = (0.0f32, 0.0f32);
+            let mut sum_sq = 0.0f64;
+            let mut count = 0u64;
+            for frame in start..end {
                 for ch in 0..channels {
-                    let sample = samples[frame * channels + ch];
-                    if sample < min {
-                        min = sample;
-                    }
-                    if sample > max {
-                        max = sample;
-                    }
+                    let s = samples[frame * channels + ch];
+                    min = min.min(s);
+                    max = max.max(s);
+                    sum_sq += (s as f64) * (s as f64);
+                    count += 1;
                 }
             }
+            let rms = if count > 0 {
+                (sum_sq / count as f64).sqrt() as f32
+            } else {
+                0.0
+            };
+            level0.push(Bucket { min, max, rms });
+        }
 
-            peaks.push((min, max));
+        let mut levels = vec![PeakLevel {
+            bucket_frames: BASE_BUCKET_FRAMES,
+            peaks: level0,
+        }];
+
+        // Fold up until a level covers the file in a handful of buckets.
+        while levels.last().unwrap().peaks.len() > 4 {
+            let prev = levels.last().unwrap();
+            let folded: Vec<Bucket> = prev
+                .peaks
+                .chunks(2)
+                .map(|pair| {
+                    let a = pair[0];
+                    let b = pair.get(1).copied().unwrap_or(a);
+                    Bucket {
+                        min: a.min.min(b.min),
+                        max: a.max.max(b.max),
+                        // RMS of the union is the quadratic mean of the halves.
+                        rms: ((a.rms * a.rms + b.rms * b.rms) / 2.0).sqrt(),
+                    }
+                })
+                .collect();
+            levels.push(PeakLevel {
+                bucket_frames: prev.bucket_frames * 2,
+                peaks: folded,
+            });
         }
 
         Self {
-            peaks,
+            levels,
             total_samples,
         }
     }
+
+    /// Aggregate the pyramid into `width` display columns spanning the sample
+    /// window `[start, end)`.
+    ///
+    /// Picks the coarsest level whose bucket covers no more than one pixel,
+    /// then merges that level's buckets into each column's min/max.
+    pub fn column_peaks(&self, start: u64, end: u64, width: usize) -> Vec<Bucket> {
+        if width == 0 || end <= start || self.levels.is_empty() {
+            return vec![Bucket::default(); width.max(1)];
+        }
+        let span = (end - start) as f64;
+        let samples_per_pixel = span / width as f64;
+
+        // Coarsest level whose bucket size is still <= samples_per_pixel.
+        let level = self
+            .levels
+            .iter()
+            .rev()
+            .find(|l| (l.bucket_frames as f64) <= samples_per_pixel)
+            .unwrap_or(&self.levels[0]);
+        let bf = level.bucket_frames as f64;
+
+        let mut out = Vec::with_capacity(width);
+        for col in 0..width {
+            let col_start = start as f64 + col as f64 * samples_per_pixel;
+            let col_end = start as f64 + (col + 1) as f64 * samples_per_pixel;
+            let b0 = (col_start / bf).floor() as usize;
+            let b1 = ((col_end / bf).ceil() as usize).max(b0 + 1);
+            let (mut mn, mut mx) = (0.0f32, 0.0f32);
+            let mut sum_sq = 0.0f32;
+            let mut n = 0f32;
+            for b in b0..b1.min(level.peaks.len()) {
+                let bucket = level.peaks[b];
+                mn = mn.min(bucket.min);
+                mx = mx.max(bucket.max);
+                sum_sq += bucket.rms * bucket.rms;
+                n += 1.0;
+            }
+            let rms = if n > 0.0 { (sum_sq / n).sqrt() } else { 0.0 };
+            out.push(Bucket { min: mn, max: mx, rms });
+        }
+        out
+    }
+}
+
+/// Map a linear amplitude magnitude to a pixel height under `mode`.
+fn amp_to_height(magnitude: f32, half_h: f32, mode: ScaleMode, db_floor: f32) -> f32 {
+    let m = magnitude.abs().min(1.0);
+    match mode {
+        ScaleMode::Linear => m * half_h,
+        ScaleMode::Db => {
+            if m <= 0.0 {
+                return 0.0;
+            }
+            let db = 20.0 * m.log10();
+            if db <= db_floor {
+                0.0
+            } else {
+                ((db - db_floor) / -db_floor).clamp(0.0, 1.0) * half_h
+            }
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -88,6 +220,23 @@ impl WaveformPeaks {
 pub enum WaveformEvent {
     /// The user scrubbed to a new position; value is the target sample index.
     Seek(u64),
+    /// The user defined or adjusted a loop region `[start, end)`.
+    SetLoopRegion(u64, u64),
+    /// The user cleared the loop region.
+    ClearLoopRegion,
+}
+
+/// What a mouse drag on the waveform is currently doing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DragMode {
+    /// Scrubbing the playhead.
+    Seek,
+    /// Drawing a new loop region from the press point.
+    NewRegion,
+    /// Dragging the region's in (start) handle.
+    RegionStart,
+    /// Dragging the region's out (end) handle.
+    RegionEnd,
 }
 
 // ---------------------------------------------------------------------------
@@ -99,8 +248,22 @@ pub struct WaveformView {
     pub peaks: WaveformPeaks,
     /// Current playback position in samples (updated by the app timer).
     pub position: u64,
+    /// First visible sample of the zoom/scroll window.
+    pub start_sample: u64,
+    /// One past the last visible sample of the zoom/scroll window.
+    pub end_sample: u64,
+    /// Linear vs logarithmic (dB) amplitude mapping.
+    pub scale_mode: ScaleMode,
+    /// Floor in dB for [`ScaleMode::Db`].
+    pub db_floor: f32,
+    /// Active loop region `[start, end)`, if any.
+    pub loop_region: Option<(u64, u64)>,
     /// True while the left mouse button is held down on the waveform.
     pub dragging: bool,
+    /// What the current drag gesture is doing.
+    drag_mode: DragMode,
+    /// The fixed anchor sample of a region drag (the non-moving edge).
+    region_anchor: u64,
     /// Last-known canvas bounds, used to convert mouse X to a sample position.
     bounds: Bounds<Pixels>,
 }
@@ -109,23 +272,97 @@ impl gpui::EventEmitter<WaveformEvent> for WaveformView {}
 
 impl WaveformView {
     pub fn new(peaks: WaveformPeaks) -> Self {
+        let end_sample = peaks.total_samples;
         Self {
             peaks,
             position: 0,
+            start_sample: 0,
+            end_sample,
+            scale_mode: ScaleMode::Linear,
+            db_floor: DEFAULT_DB_FLOOR,
+            loop_region: None,
             dragging: false,
+            drag_mode: DragMode::Seek,
+            region_anchor: 0,
             bounds: Bounds::default(),
         }
     }
 
-    /// Convert a window-space mouse position to a sample index.
+    /// Replace the displayed peaks with a freshly generated block and reset the
+    /// view to span it, so a live test signal scrolls through as a scope.
+    pub fn show_live_block(&mut self, samples: &[f32], channels: usize) {
+        self.peaks = WaveformPeaks::from_samples(samples, channels);
+        self.start_sample = 0;
+        self.end_sample = self.peaks.total_samples;
+        self.position = self.end_sample;
+    }
+
+    /// Convert a window-space mouse position to a sample index, mapped against
+    /// the currently visible window rather than the whole file.
     fn sample_from_mouse(&self, mouse_position: Point<Pixels>) -> u64 {
         let x = f32::from(mouse_position.x) - f32::from(self.bounds.origin.x);
         let width = f32::from(self.bounds.size.width);
-        if width <= 0.0 || self.peaks.total_samples == 0 {
-            return 0;
+        if width <= 0.0 || self.end_sample <= self.start_sample {
+            return self.start_sample;
+        }
+        let progress = (x / width).clamp(0.0, 1.0) as f64;
+        let span = (self.end_sample - self.start_sample) as f64;
+        self.start_sample + (progress * span) as u64
+    }
+
+    /// Pixel x for a given sample within the visible window.
+    fn x_from_sample(&self, sample: u64) -> f32 {
+        let width = f32::from(self.bounds.size.width);
+        if self.end_sample <= self.start_sample {
+            return 0.0;
+        }
+        let span = (self.end_sample - self.start_sample) as f64;
+        let p = ((sample.saturating_sub(self.start_sample)) as f64 / span).clamp(0.0, 1.0);
+        p as f32 * width
+    }
+
+    /// Classify a press: near a region handle → adjust it; shift held → new
+    /// region; otherwise seek.
+    fn begin_drag(&mut self, event: &MouseDownEvent) {
+        let sample = self.sample_from_mouse(event.position);
+        let x = f32::from(event.position.x) - f32::from(self.bounds.origin.x);
+        const HANDLE_PX: f32 = 6.0;
+
+        if let Some((lo, hi)) = self.loop_region {
+            if (x - self.x_from_sample(lo)).abs() <= HANDLE_PX {
+                self.drag_mode = DragMode::RegionStart;
+                self.region_anchor = hi;
+                return;
+            }
+            if (x - self.x_from_sample(hi)).abs() <= HANDLE_PX {
+                self.drag_mode = DragMode::RegionEnd;
+                self.region_anchor = lo;
+                return;
+            }
+        }
+
+        if event.modifiers.shift {
+            self.drag_mode = DragMode::NewRegion;
+            self.region_anchor = sample;
+            self.loop_region = Some((sample, sample));
+        } else {
+            self.drag_mode = DragMode::Seek;
+        }
+    }
+
+    /// Update the moving edge of the loop region during a drag and notify the
+    /// app. A region narrower than one base bucket is treated as a clear.
+    fn update_region(&mut self, position: Point<Pixels>, cx: &mut gpui::Context<Self>) {
+        let moving = self.sample_from_mouse(position);
+        let lo = self.region_anchor.min(moving);
+        let hi = self.region_anchor.max(moving);
+        if hi.saturating_sub(lo) < BASE_BUCKET_FRAMES as u64 {
+            self.loop_region = None;
+            cx.emit(WaveformEvent::ClearLoopRegion);
+        } else {
+            self.loop_region = Some((lo, hi));
+            cx.emit(WaveformEvent::SetLoopRegion(lo, hi));
         }
-        let progress = (x / width).clamp(0.0, 1.0);
-        (progress as f64 * self.peaks.total_samples as f64) as u64
     }
 }
 
@@ -133,6 +370,11 @@ impl Render for WaveformView {
     fn render(&mut self, _window: &mut Window, cx: &mut gpui::Context<Self>) -> impl IntoElement {
         let peaks = self.peaks.clone();
         let position = self.position;
+        let start = self.start_sample;
+        let end = self.end_sample;
+        let scale_mode = self.scale_mode;
+        let db_floor = self.db_floor;
+        let loop_region = self.loop_region;
         let weak = cx.weak_entity();
 
         div()
@@ -145,15 +387,26 @@ impl Render for WaveformView {
                 MouseButton::Left,
                 cx.listener(|this, event: &MouseDownEvent, _window, cx| {
                     this.dragging = true;
-                    this.position = this.sample_from_mouse(event.position);
-                    cx.emit(WaveformEvent::Seek(this.position));
+                    this.begin_drag(event);
+                    match this.drag_mode {
+                        DragMode::Seek => {
+                            this.position = this.sample_from_mouse(event.position);
+                            cx.emit(WaveformEvent::Seek(this.position));
+                        }
+                        _ => this.update_region(event.position, cx),
+                    }
                     cx.notify();
                 }),
             )
             .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _window, cx| {
                 if event.dragging() {
-                    this.position = this.sample_from_mouse(event.position);
-                    cx.emit(WaveformEvent::Seek(this.position));
+                    match this.drag_mode {
+                        DragMode::Seek => {
+                            this.position = this.sample_from_mouse(event.position);
+                            cx.emit(WaveformEvent::Seek(this.position));
+                        }
+                        _ => this.update_region(event.position, cx),
+                    }
                     cx.notify();
                 }
             }))
@@ -170,7 +423,10 @@ impl Render for WaveformView {
                         }
                     },
                     move |bounds, _prepaint, window, _cx| {
-                        draw_waveform(window, bounds, &peaks, position);
+                        draw_waveform(
+                            window, bounds, &peaks, position, start, end, scale_mode, db_floor,
+                            loop_region,
+                        );
                     },
                 )
                 .w_full()
@@ -200,11 +456,17 @@ fn paint_rect(window: &mut Window, x: f32, y: f32, w: f32, h: f32, color: Hsla)
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_waveform(
     window: &mut Window,
     bounds: Bounds<Pixels>,
     peaks: &WaveformPeaks,
     position: u64,
+    start: u64,
+    end: u64,
+    scale_mode: ScaleMode,
+    db_floor: f32,
+    loop_region: Option<(u64, u64)>,
 ) {
     let width = f32::from(bounds.size.width);
     let height = f32::from(bounds.size.height);
@@ -213,29 +475,59 @@ fn draw_waveform(
     let mid_y = origin_y + height / 2.0;
     let half_h = height / 2.0 * 0.9;
 
-    let num_cols = peaks.peaks.len().max(1);
-    let waveform_color: Hsla = rgb(0x4a9eff).into();
+    let num_cols = width.floor().max(1.0) as usize;
+    let columns = peaks.column_peaks(start, end, num_cols);
+    let peak_color: Hsla = rgb(0x3a6ea5).into();
+    let rms_color: Hsla = rgb(0x7fc4ff).into();
     let center_color: Hsla = rgb(0x2a4a6e).into();
     let playhead_color: Hsla = rgb(0xffffff).into();
 
-    // Draw waveform bars.
-    for (i, &(min_val, max_val)) in peaks.peaks.iter().enumerate() {
-        let x = origin_x + (i as f32 / num_cols as f32) * width;
-        let bar_width = (width / num_cols as f32).max(1.0);
+    // Draw each column: a lighter peak envelope with a brighter RMS body.
+    let col_width = (width / num_cols as f32).max(1.0);
+    for (i, bucket) in columns.iter().enumerate() {
+        let x = origin_x + i as f32 * col_width;
 
-        let top = mid_y - max_val.abs().min(1.0) * half_h;
-        let bottom = mid_y + min_val.abs().min(1.0) * half_h;
-        let bar_height = (bottom - top).max(1.0);
+        let up = amp_to_height(bucket.max, half_h, scale_mode, db_floor);
+        let down = amp_to_height(bucket.min, half_h, scale_mode, db_floor);
+        let top = mid_y - up;
+        let bar_height = (up + down).max(1.0);
+        paint_rect(window, x, top, col_width.max(1.0), bar_height, peak_color);
 
-        paint_rect(window, x, top, bar_width - 0.5, bar_height, waveform_color);
+        let rms_h = amp_to_height(bucket.rms, half_h, scale_mode, db_floor);
+        if rms_h > 0.0 {
+            paint_rect(
+                window,
+                x,
+                mid_y - rms_h,
+                col_width.max(1.0),
+                (rms_h * 2.0).max(1.0),
+                rms_color,
+            );
+        }
     }
 
     // Draw center line.
     paint_rect(window, origin_x, mid_y - 0.5, width, 1.0, center_color);
 
-    // Draw playhead.
-    if peaks.total_samples > 0 {
-        let progress = position as f32 / peaks.total_samples as f32;
+    // Draw the loop region: a translucent overlay with in/out handle lines.
+    if let Some((lo, hi)) = loop_region {
+        if end > start && hi > start && lo < end {
+            let span = (end - start) as f32;
+            let lo_x = origin_x + ((lo.saturating_sub(start)) as f32 / span).clamp(0.0, 1.0) * width;
+            let hi_x = origin_x + ((hi.saturating_sub(start)) as f32 / span).clamp(0.0, 1.0) * width;
+            let region_fill: Hsla = rgb(0xffd27f).into();
+            let mut fill = region_fill;
+            fill.a = 0.12;
+            paint_rect(window, lo_x, origin_y, (hi_x - lo_x).max(1.0), height, fill);
+            let handle_color: Hsla = rgb(0xffd27f).into();
+            paint_rect(window, lo_x, origin_y, 2.0, height, handle_color);
+            paint_rect(window, hi_x - 2.0, origin_y, 2.0, height, handle_color);
+        }
+    }
+
+    // Draw playhead if it falls within the visible window.
+    if end > start && position >= start && position < end {
+        let progress = (position - start) as f32 / (end - start) as f32;
         let playhead_x = origin_x + progress.clamp(0.0, 1.0) * width;
         paint_rect(window, playhead_x, origin_y, 2.0, height, playhead_color);
     }